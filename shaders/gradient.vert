@@ -0,0 +1,8 @@
+uniform mat4 u_view_projection;
+in vec3 a_position;
+in vec3 a_color;
+out vec3 color;
+void main() {
+    color = a_color;
+    gl_Position = u_view_projection * vec4(a_position, 1.0);
+}
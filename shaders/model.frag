@@ -0,0 +1,25 @@
+precision mediump float;
+uniform vec4 u_base_color;
+uniform bool u_has_texture;
+uniform sampler2D u_texture;
+in vec3 normal;
+in vec2 uv;
+out vec4 color;
+
+// The framebuffer is not sRGB, so light in linear space and encode by hand
+vec3 linear_to_srgb(vec3 linear) {
+    vec3 curve = 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055;
+    return mix(linear * 12.92, curve, step(0.0031308, linear));
+}
+
+void main() {
+    // sRGB textures are decoded to linear by the sampler
+    vec4 base_color = u_base_color;
+    if (u_has_texture) {
+        base_color *= texture(u_texture, uv);
+    }
+    // One fixed light from the upper left, plus ambient so nothing is black
+    vec3 light = normalize(vec3(-0.4, 0.8, 0.6));
+    float diffuse = max(dot(normalize(normal), light), 0.0);
+    color = vec4(linear_to_srgb(base_color.rgb * (0.15 + 0.85 * diffuse)), 1.0);
+}
@@ -0,0 +1,13 @@
+uniform mat4 u_model;
+uniform mat4 u_view_projection;
+in vec3 a_position;
+in vec3 a_normal;
+in vec2 a_uv;
+out vec3 normal;
+out vec2 uv;
+void main() {
+    // Only right for rotation and uniform scale, which is all the viewer uses
+    normal = mat3(u_model[0].xyz, u_model[1].xyz, u_model[2].xyz) * a_normal;
+    uv = a_uv;
+    gl_Position = u_view_projection * u_model * vec4(a_position, 1.0);
+}
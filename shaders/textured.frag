@@ -0,0 +1,12 @@
+precision mediump float;
+uniform sampler2D u_texture;
+in vec2 uv;
+out vec4 frag_color;
+// Samples are taken to be linear; the framebuffer is not sRGB
+vec3 linear_to_srgb(vec3 linear) {
+    vec3 curve = 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055;
+    return mix(linear * 12.92, curve, step(0.0031308, linear));
+}
+void main() {
+    frag_color = vec4(linear_to_srgb(texture(u_texture, uv).rgb), 1.0);
+}
@@ -0,0 +1,9 @@
+uniform mat4 u_view_projection;
+in vec3 a_position;
+in vec2 a_uv;
+out vec2 uv;
+void main() {
+    // Reach past the texture's edges to show the wrap mode
+    uv = a_uv * 2.0 - 0.5;
+    gl_Position = u_view_projection * vec4(a_position, 1.0);
+}
@@ -0,0 +1,79 @@
+//! Where the demos find their shaders, textures and models at run time.
+//!
+//! Nothing is embedded in the binary, so the files are looked up in an asset
+//! root: a directory holding the crate's `shaders` and `assets` directories.
+//! `cargo run` finds the checkout above `target/`, an installed binary can
+//! be pointed at a copy with [`ROOT_ENV`].
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable naming the asset root, which skips the search.
+pub const ROOT_ENV: &str = "WINIT_TEST_ASSETS";
+
+/// The asset root: [`ROOT_ENV`] if set, else the first of the executable's
+/// directory, its ancestors and the working directory that holds `shaders`
+/// and `assets`. Falls back to the working directory, so a missing file is
+/// reported relative to it. Resolved once per process.
+pub fn root() -> &'static Path {
+    static ROOT: OnceLock<PathBuf> = OnceLock::new();
+    ROOT.get_or_init(|| {
+        let cwd = env::current_dir().ok();
+        let root = match env::var_os(ROOT_ENV) {
+            Some(root) => PathBuf::from(root),
+            None => {
+                let exe = env::current_exe().ok();
+                find_root(exe.as_deref(), cwd.as_deref())
+                    .or(cwd)
+                    .unwrap_or_default()
+            }
+        };
+        log::debug!("Loading assets from {}", root.display());
+        root
+    })
+}
+
+/// The first asset root above `exe`, or else `cwd` if it is one.
+fn find_root(exe: Option<&Path>, cwd: Option<&Path>) -> Option<PathBuf> {
+    let exe_dirs = exe
+        .and_then(Path::parent)
+        .into_iter()
+        .flat_map(Path::ancestors);
+    exe_dirs
+        .chain(cwd)
+        .find(|dir| is_root(dir))
+        .map(Path::to_owned)
+}
+
+fn is_root(dir: &Path) -> bool {
+    dir.join("shaders").is_dir() && dir.join("assets").is_dir()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    #[test]
+    fn finds_the_checkout_above_the_build_directory() {
+        let exe = Path::new(MANIFEST_DIR).join("target/debug/winit-test");
+        let found = find_root(Some(&exe), None);
+        assert_eq!(found.as_deref(), Some(Path::new(MANIFEST_DIR)));
+    }
+
+    #[test]
+    fn falls_back_to_the_working_directory() {
+        let exe = env::temp_dir().join("winit-test");
+        let found = find_root(Some(&exe), Some(Path::new(MANIFEST_DIR)));
+        assert_eq!(found.as_deref(), Some(Path::new(MANIFEST_DIR)));
+    }
+
+    #[test]
+    fn finds_nothing_without_shaders_and_assets() {
+        let src = Path::new(MANIFEST_DIR).join("src");
+        assert_eq!(find_root(None, Some(&src)), None);
+        assert_eq!(find_root(None, None), None);
+    }
+}
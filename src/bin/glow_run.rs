@@ -1,264 +0,0 @@
-use std::collections::HashMap;
-use std::error::Error;
-use std::num::NonZeroU32;
-
-use glow::*;
-use glutin::config::ConfigTemplateBuilder;
-use glutin::context::{ContextApi, ContextAttributesBuilder};
-use glutin::display::GetGlDisplay;
-use glutin::prelude::*;
-use glutin_winit::{DisplayBuilder, GlWindow};
-use std::sync::Arc;
-use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
-use winit::event::WindowEvent;
-use winit::event_loop::{ActiveEventLoop, EventLoop};
-use winit::raw_window_handle::HasWindowHandle;
-use winit::window::{Window, WindowId};
-
-fn main() -> Result<(), Box<dyn Error>> {
-    let event_loop = EventLoop::new()?;
-    let mut app = Application::new();
-    event_loop.run_app(&mut app).map_err(Into::into)
-}
-
-struct Application {
-    template: Option<glutin::config::Config>,
-    display: Option<glutin::display::Display>,
-    windows: HashMap<WindowId, WindowState>,
-}
-
-struct WindowState {
-    window: Window,
-    gl_context: glutin::context::PossiblyCurrentContext,
-    gl_surface: glutin::surface::Surface<glutin::surface::WindowSurface>,
-    gl: Arc<glow::Context>,
-}
-
-impl Application {
-    fn new() -> Self {
-        Self {
-            template: None,
-            display: None,
-            windows: HashMap::new(),
-        }
-    }
-
-    fn create_window(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Box<dyn Error>> {
-        let window_attributes = Window::default_attributes()
-            .with_title("Glow OpenGL Window")
-            .with_inner_size(PhysicalSize::new(800, 600));
-
-        let template = ConfigTemplateBuilder::new()
-            .with_alpha_size(8)
-            .with_transparency(false);
-
-        let display_builder = DisplayBuilder::new().with_window_attributes(Some(window_attributes));
-
-        let (window, gl_config) = display_builder
-            .build(event_loop, template, |configs| {
-                configs
-                    .reduce(|accum, config| {
-                        if config.num_samples() > accum.num_samples() {
-                            config
-                        } else {
-                            accum
-                        }
-                    })
-                    .unwrap()
-            })
-            .unwrap();
-
-        let raw_window_handle = window
-            .as_ref()
-            .and_then(|window| window.window_handle().ok().map(|h| h.as_raw()));
-        let gl_display = gl_config.display();
-        //let raw_window_handle = window.raw_window_handle();
-        let window = window.unwrap();
-
-        let attrs = window.build_surface_attributes(Default::default()).unwrap();
-        let gl_surface = unsafe {
-            gl_display
-                .create_window_surface(&gl_config, &attrs)
-                .unwrap()
-        };
-
-        //let context_attributes = ContextAttributesBuilder::new().build(Some(raw_window_handle));
-        let context_attributes = ContextAttributesBuilder::new()
-            .with_context_api(ContextApi::OpenGl(Some(glutin::context::Version {
-                major: 4,
-                minor: 1,
-            })))
-            .build(raw_window_handle);
-
-        let gl_context = unsafe { gl_display.create_context(&gl_config, &context_attributes)? };
-
-        let gl_context = gl_context.make_current(&gl_surface)?;
-
-        let gl = Arc::new(unsafe {
-            glow::Context::from_loader_function_cstr(|s| gl_display.get_proc_address(s).cast())
-        });
-
-        unsafe {
-            let vertex_array = gl
-                .create_vertex_array()
-                .expect("Cannot create vertex array");
-            gl.bind_vertex_array(Some(vertex_array));
-
-            let program = gl.create_program().expect("Cannot create program");
-
-            let (vertex_shader_source, fragment_shader_source) = (
-                r#"const vec2 verts[3] = vec2[3](
-                vec2(0.5f, 1.0f),
-                vec2(0.0f, 0.0f),
-                vec2(1.0f, 0.0f)
-            );
-            out vec2 vert;
-            void main() {
-                vert = verts[gl_VertexID];
-                gl_Position = vec4(vert - 0.5, 0.0, 1.0);
-            }"#,
-                r#"precision mediump float;
-            in vec2 vert;
-            out vec4 color;
-            void main() {
-                color = vec4(vert, 0.5, 1.0);
-            }"#,
-            );
-
-            let shader_sources = [
-                (glow::VERTEX_SHADER, vertex_shader_source),
-                (glow::FRAGMENT_SHADER, fragment_shader_source),
-            ];
-
-            let mut shaders = Vec::with_capacity(shader_sources.len());
-
-            for (shader_type, shader_source) in shader_sources.iter() {
-                let shader = gl
-                    .create_shader(*shader_type)
-                    .expect("Cannot create shader");
-                gl.shader_source(shader, &format!("{}\n{}", "#version 410", shader_source));
-                gl.compile_shader(shader);
-                if !gl.get_shader_compile_status(shader) {
-                    panic!("{}", gl.get_shader_info_log(shader));
-                }
-                gl.attach_shader(program, shader);
-                shaders.push(shader);
-            }
-
-            gl.link_program(program);
-            if !gl.get_program_link_status(program) {
-                panic!("{}", gl.get_program_info_log(program));
-            }
-
-            for shader in shaders {
-                gl.detach_shader(program, shader);
-                gl.delete_shader(shader);
-            }
-
-            gl.use_program(Some(program));
-            gl.clear_color(0.1, 0.2, 0.3, 1.0);
-        }
-
-        let window_id = window.id();
-        let window_state = WindowState {
-            window,
-            gl_context,
-            gl_surface,
-            gl,
-        };
-
-        self.windows.insert(window_id, window_state);
-        self.display = Some(gl_display);
-        self.template = Some(gl_config);
-
-        Ok(())
-    }
-}
-
-impl ApplicationHandler for Application {
-    fn window_event(
-        &mut self,
-        event_loop: &ActiveEventLoop,
-        window_id: WindowId,
-        event: WindowEvent,
-    ) {
-        let window_state = match self.windows.get_mut(&window_id) {
-            Some(window) => window,
-            None => return,
-        };
-
-        match event {
-            WindowEvent::CloseRequested => {
-                self.windows.remove(&window_id);
-                if self.windows.is_empty() {
-                    event_loop.exit();
-                }
-            }
-            WindowEvent::RedrawRequested => unsafe {
-                //let window_state = self.windows.get_mut(&window_id).unwrap();
-
-                // Begin egui frame
-                // let raw_input = window_state
-                //     .egui_state
-                //     .take_egui_input(&window_state.window);
-                // let full_output = window_state.egui_ctx.run(raw_input, |ctx| {
-                //     egui::CentralPanel::default().show(ctx, |ui| {
-                //         ui.heading("My egui Application");
-                //         ui.horizontal(|ui| {
-                //             let name_label = ui.label("Your name: ");
-                //             ui.text_edit_singleline(&mut self.name)
-                //                 .labelled_by(name_label.id);
-                //         });
-                //         ui.add(egui::Slider::new(&mut self.age, 0..=120).text("age"));
-                //         if ui.button("Increment").clicked() {
-                //             self.age += 1;
-                //         }
-                //         ui.label(format!("Hello '{}', age {}", self.name, self.age));
-                //     });
-                // });
-
-                // Render egui on top
-                // let clipped_primitives = window_state
-                //     .egui_ctx
-                //     .tessellate(full_output.shapes, full_output.pixels_per_point);
-
-                // let size = window_state.window.inner_size();
-                // window_state.egui_painter.paint_primitives(
-                //     [size.width, size.height],
-                //     full_output.pixels_per_point,
-                //     &clipped_primitives,
-                // );
-
-                // window_state
-                //     .egui_state
-                //     .handle_platform_output(&window_state.window, full_output.platform_output);
-
-                window_state.gl.clear(glow::COLOR_BUFFER_BIT);
-                window_state.gl.draw_arrays(glow::TRIANGLES, 0, 3);
-                window_state
-                    .gl_surface
-                    .swap_buffers(&window_state.gl_context)
-                    .unwrap();
-            },
-            WindowEvent::Resized(size) => {
-                if size.width != 0 && size.height != 0 {
-                    window_state.gl_surface.resize(
-                        &window_state.gl_context,
-                        NonZeroU32::new(size.width).unwrap(),
-                        NonZeroU32::new(size.height).unwrap(),
-                    );
-                    window_state.window.request_redraw();
-                }
-            }
-            _ => {}
-        }
-    }
-
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        if self.windows.is_empty() {
-            self.create_window(event_loop)
-                .expect("Failed to create window");
-        }
-    }
-}
@@ -0,0 +1,361 @@
+//! An orbit camera: view and projection matrices for a camera circling a
+//! target point, and mouse controls to orbit, pan and zoom it.
+//!
+//! The projection follows the viewport's aspect ratio, so scenes keep their
+//! proportions when the window is resized. [`OrbitControls`] drives a camera
+//! from winit events; demos that get their input through egui call
+//! [`OrbitCamera::orbit`], [`OrbitCamera::pan`] and [`OrbitCamera::zoom`]
+//! directly.
+
+use std::f32::consts::FRAC_PI_2;
+use std::fmt;
+
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+
+use crate::math::{self, Mat4, Vec3};
+
+/// Radians of rotation per pixel dragged.
+const ORBIT_SPEED: f32 = 0.01;
+
+/// Distance factor per scroll step; scrolling up moves closer.
+const ZOOM_STEP: f32 = 0.9;
+
+/// Pixels of a pixel-precise scroll (touchpads) that count as one step.
+const PIXELS_PER_SCROLL_STEP: f32 = 50.0;
+
+/// Keeps the camera off the poles, where the view's up direction flips.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+const MIN_DISTANCE: f32 = 0.1;
+const MAX_DISTANCE: f32 = 50.0;
+
+/// How view space is mapped to clip space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Projection {
+    #[default]
+    Perspective,
+    /// Parallel projection, sized to show what the perspective projection
+    /// shows at the target's distance.
+    Orthographic,
+}
+
+impl Projection {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Perspective => Self::Orthographic,
+            Self::Orthographic => Self::Perspective,
+        }
+    }
+}
+
+impl fmt::Display for Projection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Perspective => "perspective",
+            Self::Orthographic => "orthographic",
+        })
+    }
+}
+
+/// A camera looking at `target` from `distance` away, rotated `yaw` radians
+/// about the y axis and `pitch` radians up from the horizon.
+///
+/// The default looks down -z at the origin, from far enough back that the
+/// clip-space square from -0.5 to 0.5 fills half the viewport's height.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCamera {
+    pub target: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    /// Vertical field of view in radians; also sizes the orthographic view.
+    pub fov_y: f32,
+    pub projection: Projection,
+    /// Viewport size in pixels, set with [`OrbitCamera::set_viewport`].
+    viewport: [u32; 2],
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        let fov_y = 45_f32.to_radians();
+        Self {
+            target: [0.0; 3],
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 1.0 / (fov_y / 2.0).tan(),
+            fov_y,
+            projection: Projection::Perspective,
+            viewport: [1, 1],
+        }
+    }
+}
+
+impl OrbitCamera {
+    /// A camera at `distance` from the origin, rotated by `yaw` and `pitch`.
+    pub fn new(yaw: f32, pitch: f32, distance: f32) -> Self {
+        Self {
+            yaw,
+            pitch,
+            distance,
+            ..Self::default()
+        }
+    }
+
+    /// Call when the viewport is resized, so the projection keeps the scene's
+    /// aspect ratio.
+    pub fn set_viewport(&mut self, [width, height]: [u32; 2]) {
+        self.viewport = [width.max(1), height.max(1)];
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.viewport[0] as f32 / self.viewport[1] as f32
+    }
+
+    /// The camera's rotation, from world to view space.
+    fn rotation(&self) -> Mat4 {
+        Mat4::rotation_x(self.pitch) * Mat4::rotation_y(-self.yaw)
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::translation([0.0, 0.0, -self.distance])
+            * self.rotation()
+            * Mat4::translation(math::scale(self.target, -1.0))
+    }
+
+    pub fn projection(&self) -> Mat4 {
+        // Far enough to keep a unit-sized scene in view at the largest distance
+        let (near, far) = (0.1, MAX_DISTANCE * 2.0);
+        match self.projection {
+            Projection::Perspective => Mat4::perspective(self.fov_y, self.aspect(), near, far),
+            Projection::Orthographic => {
+                let half_height = self.half_height();
+                let half_width = half_height * self.aspect();
+                Mat4::orthographic(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    far,
+                )
+            }
+        }
+    }
+
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection() * self.view()
+    }
+
+    /// Half the height of the view at the target's distance, in world units.
+    fn half_height(&self) -> f32 {
+        self.distance * (self.fov_y / 2.0).tan()
+    }
+
+    /// Rotate around the target by a drag of `[dx, dy]` pixels.
+    pub fn orbit(&mut self, [dx, dy]: [f32; 2]) {
+        self.yaw -= dx * ORBIT_SPEED;
+        self.pitch = (self.pitch + dy * ORBIT_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Move the target so the point under the cursor follows a drag of
+    /// `[dx, dy]` pixels.
+    pub fn pan(&mut self, [dx, dy]: [f32; 2]) {
+        let units_per_pixel = 2.0 * self.half_height() / self.viewport[1] as f32;
+        // The inverse rotation takes view space axes to world space
+        let to_world = Mat4::rotation_y(self.yaw) * Mat4::rotation_x(-self.pitch);
+        let right = to_world.transform_vector([1.0, 0.0, 0.0]);
+        let up = to_world.transform_vector([0.0, 1.0, 0.0]);
+        let offset = math::add(
+            math::scale(right, -dx * units_per_pixel),
+            math::scale(up, dy * units_per_pixel),
+        );
+        self.target = math::add(self.target, offset);
+    }
+
+    /// Move closer by `steps` scroll steps, or further away when negative.
+    pub fn zoom(&mut self, steps: f32) {
+        self.distance = (self.distance * ZOOM_STEP.powf(steps)).clamp(MIN_DISTANCE, MAX_DISTANCE);
+    }
+}
+
+/// Orbits a camera while the left button is dragged, pans it with the right
+/// or middle button and zooms with the scroll wheel.
+///
+/// Drags only start, and scrolling only zooms, while egui doesn't want the
+/// pointer, so using the UI never moves the camera.
+#[derive(Debug, Default)]
+pub struct OrbitControls {
+    cursor: Option<[f32; 2]>,
+    drag: Option<Drag>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Drag {
+    Orbit,
+    Pan,
+}
+
+impl OrbitControls {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply `event` to `camera`, returning `true` when the camera moved.
+    ///
+    /// `egui_wants_pointer` is `egui::Context::wants_pointer_input` after egui
+    /// has seen the event.
+    pub fn on_window_event(
+        &mut self,
+        camera: &mut OrbitCamera,
+        event: &WindowEvent,
+        egui_wants_pointer: bool,
+    ) -> bool {
+        match event {
+            WindowEvent::MouseInput { state, button, .. } => {
+                let drag = match button {
+                    MouseButton::Left => Drag::Orbit,
+                    MouseButton::Right | MouseButton::Middle => Drag::Pan,
+                    _ => return false,
+                };
+                match state {
+                    ElementState::Pressed if !egui_wants_pointer && self.drag.is_none() => {
+                        self.drag = Some(drag);
+                    }
+                    ElementState::Released if self.drag == Some(drag) => self.drag = None,
+                    _ => {}
+                }
+                false
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = [position.x as f32, position.y as f32];
+                let last = self.cursor.replace(position);
+                let (Some(drag), Some(last)) = (self.drag, last) else {
+                    return false;
+                };
+                let delta = [position[0] - last[0], position[1] - last[1]];
+                match drag {
+                    Drag::Orbit => camera.orbit(delta),
+                    Drag::Pan => camera.pan(delta),
+                }
+                true
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor = None;
+                false
+            }
+            WindowEvent::MouseWheel { delta, .. } if !egui_wants_pointer => {
+                let steps = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(delta) => delta.y as f32 / PIXELS_PER_SCROLL_STEP,
+                };
+                camera.zoom(steps);
+                steps != 0.0
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Vec3, expected: Vec3) {
+        let close = actual
+            .iter()
+            .zip(expected)
+            .all(|(a, e)| (a - e).abs() < 1e-4);
+        assert!(close, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn default_frames_the_unit_square() {
+        for projection in [Projection::Perspective, Projection::Orthographic] {
+            let camera = OrbitCamera {
+                projection,
+                ..OrbitCamera::default()
+            };
+            let view_projection = camera.view_projection();
+            let [x, y, _] = view_projection.project_point([0.0; 3]);
+            assert_close([x, y, 0.0], [0.0; 3]);
+            let [x, y, _] = view_projection.project_point([0.5, 0.5, 0.0]);
+            assert_close([x, y, 0.0], [0.5, 0.5, 0.0]);
+        }
+    }
+
+    #[test]
+    fn view_puts_the_eye_at_the_origin() {
+        let camera = OrbitCamera {
+            target: [1.0, 2.0, 3.0],
+            ..OrbitCamera::new(0.8, 0.3, 4.0)
+        };
+        let (sin_yaw, cos_yaw) = camera.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = camera.pitch.sin_cos();
+        let eye = math::add(
+            camera.target,
+            math::scale(
+                [sin_yaw * cos_pitch, sin_pitch, cos_yaw * cos_pitch],
+                camera.distance,
+            ),
+        );
+        assert_close(camera.view().transform_point(eye), [0.0; 3]);
+        assert_close(
+            camera.view().transform_point(camera.target),
+            [0.0, 0.0, -4.0],
+        );
+    }
+
+    #[test]
+    fn aspect_follows_the_viewport() {
+        let mut camera = OrbitCamera::default();
+        camera.set_viewport([800, 400]);
+        assert_eq!(camera.aspect(), 2.0);
+        let [x, y, _] = camera.view_projection().project_point([0.5, 0.5, 0.0]);
+        assert_close([x, y, 0.0], [0.25, 0.5, 0.0]);
+
+        // A minimized window must not divide by zero
+        camera.set_viewport([0, 0]);
+        assert_eq!(camera.aspect(), 1.0);
+    }
+
+    #[test]
+    fn orbit_clamps_pitch_short_of_the_poles() {
+        let mut camera = OrbitCamera::default();
+        camera.orbit([100.0, 0.0]);
+        assert_eq!(camera.yaw, -1.0);
+        camera.orbit([0.0, 1e6]);
+        assert_eq!(camera.pitch, MAX_PITCH);
+        camera.orbit([0.0, -1e6]);
+        assert_eq!(camera.pitch, -MAX_PITCH);
+    }
+
+    #[test]
+    fn zoom_clamps_distance() {
+        let mut camera = OrbitCamera::new(0.0, 0.0, 2.0);
+        camera.zoom(1.0);
+        assert!((camera.distance - 1.8).abs() < 1e-6);
+        camera.zoom(-1.0);
+        assert!((camera.distance - 2.0).abs() < 1e-6);
+        camera.zoom(1000.0);
+        assert_eq!(camera.distance, MIN_DISTANCE);
+        camera.zoom(-1000.0);
+        assert_eq!(camera.distance, MAX_DISTANCE);
+    }
+
+    #[test]
+    fn pan_keeps_the_dragged_point_under_the_cursor() {
+        let mut camera = OrbitCamera::new(0.5, -0.4, 3.0);
+        camera.set_viewport([200, 100]);
+        let point = camera.target;
+        // 10 pixels right and 5 down is 0.1 and -0.1 in NDC
+        camera.pan([10.0, 5.0]);
+        let [x, y, _] = camera.view_projection().project_point(point);
+        assert_close([x, y, 0.0], [0.1, -0.1, 0.0]);
+    }
+
+    #[test]
+    fn projection_toggles() {
+        assert_eq!(Projection::Perspective.toggled(), Projection::Orthographic);
+        assert_eq!(Projection::Orthographic.toggled(), Projection::Perspective);
+    }
+}
@@ -0,0 +1,131 @@
+//! Reading rendered frames back from the GPU and saving them as screenshots.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use glow::HasContext;
+use image::RgbaImage;
+
+/// What goes into a screenshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenshotLayers {
+    /// Only the app's own rendering, captured before egui paints over it.
+    Scene,
+    /// The frame as shown, egui included.
+    SceneAndUi,
+}
+
+/// Read the currently bound framebuffer into an image.
+///
+/// GL's origin is the bottom-left corner, so rows are flipped to give the
+/// usual top-down image layout.
+pub fn read_framebuffer(gl: &glow::Context, [width, height]: [u32; 2]) -> RgbaImage {
+    let row_len = width as usize * 4;
+    let mut pixels = vec![0_u8; row_len * height as usize];
+    unsafe {
+        gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+        gl.read_pixels(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelPackData::Slice(Some(&mut pixels)),
+        );
+    }
+
+    let flipped = pixels
+        .chunks_exact(row_len)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+    RgbaImage::from_raw(width, height, flipped).expect("buffer matches image size")
+}
+
+/// Save `image` as `screenshot-<UTC timestamp>.png` in the working directory
+/// and return the path it was written to.
+///
+/// The window's alpha channel is whatever the compositor ignores, so it is
+/// made opaque first; otherwise image viewers show a see-through picture.
+pub fn save_screenshot(mut image: RgbaImage) -> Result<PathBuf, image::ImageError> {
+    for pixel in image.pixels_mut() {
+        pixel[3] = u8::MAX;
+    }
+    let path = PathBuf::from(format!("screenshot-{}.png", timestamp()));
+    image.save(&path)?;
+    Ok(path)
+}
+
+/// The current UTC time as `YYYYMMDD-HHMMSS-mmm`, which sorts chronologically.
+fn timestamp() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format_timestamp(since_epoch)
+}
+
+fn format_timestamp(since_epoch: Duration) -> String {
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}-{:03}",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Days since 1970-01-01 to a proleptic Gregorian `(year, month, day)`, after
+/// Howard Hinnant's algorithm of the same name.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_from_days_at_the_epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn civil_from_days_on_leap_days() {
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+        // Divisible by 400, so a leap year after all
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    }
+
+    #[test]
+    fn civil_from_days_skips_february_29_in_common_years() {
+        assert_eq!(civil_from_days(19_416), (2023, 2, 28));
+        assert_eq!(civil_from_days(19_417), (2023, 3, 1));
+        // Divisible by 100 but not 400
+        assert_eq!(civil_from_days(47_540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(Duration::ZERO), "19700101-000000-000");
+        let last_millisecond = Duration::from_millis((19_783 * 86_400 - 1) * 1000 + 999);
+        assert_eq!(format_timestamp(last_millisecond), "20240229-235959-999");
+    }
+}
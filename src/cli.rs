@@ -0,0 +1,262 @@
+//! Command-line options shared by the demos, parsed with clap.
+//!
+//! Each demo declares its own `Args` and flattens [`CommonArgs`] into it,
+//! plus [`GlArgs`] for the GL demos, so `--help` lists everything and invalid
+//! values are reported before any window is opened.
+
+use std::path::PathBuf;
+
+use clap::builder::TypedValueParser;
+use log::LevelFilter;
+use winit::dpi::PhysicalSize;
+use winit::window::{Fullscreen, Window, WindowAttributes};
+
+use crate::gl_config::ConfigRequirements;
+use crate::gl_window::{GlApi, VsyncMode};
+use crate::headless::HeadlessOptions;
+
+/// Options every demo accepts.
+#[derive(Debug, Clone, clap::Args)]
+pub struct CommonArgs {
+    /// Window size, or image size with --headless, in pixels
+    #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "800x600", value_parser = parse_size)]
+    pub size: [u32; 2],
+
+    /// Open the window borderless fullscreen on the current monitor
+    #[arg(long, conflicts_with = "headless")]
+    pub fullscreen: bool,
+
+    /// Starting color, as R,G,B from 0 to 1 or as #RRGGBB: the background in
+    /// glow and softbuffer, the color picker's color in egui-manual and eframe
+    #[arg(long, value_name = "COLOR", value_parser = parse_color)]
+    pub color: Option<[f32; 3]>,
+
+    /// Log level; module filters in RUST_LOG still apply
+    #[arg(long, value_name = "LEVEL", value_parser = clap::builder::PossibleValuesParser::new(
+        ["off", "error", "warn", "info", "debug", "trace"],
+    ).map(|level| level.parse::<LevelFilter>().expect("listed levels parse")))]
+    pub log_level: Option<LevelFilter>,
+
+    #[command(flatten)]
+    pub headless: HeadlessArgs,
+}
+
+impl CommonArgs {
+    /// Set up `env_logger` from `RUST_LOG`, with `--log-level` as the default
+    /// level.
+    pub fn init_logging(&self) {
+        let mut builder = env_logger::Builder::from_default_env();
+        if let Some(level) = self.log_level {
+            builder.filter_level(level);
+        }
+        builder.init();
+    }
+
+    /// The attributes for the demo's window, sized and made fullscreen as
+    /// asked. The title is left to the demo.
+    pub fn window_attributes(&self) -> WindowAttributes {
+        let [width, height] = self.size;
+        Window::default_attributes()
+            .with_inner_size(PhysicalSize::new(width, height))
+            .with_fullscreen(self.fullscreen.then_some(Fullscreen::Borderless(None)))
+    }
+
+    /// `--headless` and its options, `None` meaning windowed mode.
+    pub fn headless(&self) -> Option<HeadlessOptions> {
+        self.headless.headless.then(|| HeadlessOptions {
+            size: self.size,
+            frames: self.headless.frames,
+            output: self.headless.output.clone(),
+        })
+    }
+}
+
+/// `--headless [--frames N] [--output PATH]`, see [`crate::headless`].
+#[derive(Debug, Clone, clap::Args)]
+pub struct HeadlessArgs {
+    /// Render offscreen and write the last frame to a PNG instead of opening
+    /// a window
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Frames to render with --headless
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "headless",
+    )]
+    pub frames: u32,
+
+    /// Where --headless writes its PNG
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "out.png",
+        requires = "headless"
+    )]
+    pub output: PathBuf,
+}
+
+/// Options of the demos that create their own GL context.
+#[derive(Debug, Clone, clap::Args)]
+pub struct GlArgs {
+    /// Context API to request, e.g. gl4.1, gl3.3 or gles3.0; repeat to try
+    /// several in order [default: gl4.1 gl3.3 gles3.0 gles2.0]
+    #[arg(long = "gl", value_name = "API")]
+    pub apis: Vec<GlApi>,
+
+    /// Multisample anti-aliasing samples per pixel: 0, 2, 4, 8 or 16
+    #[arg(long, value_name = "SAMPLES", value_parser = parse_msaa, conflicts_with = "headless")]
+    pub msaa: Option<u8>,
+
+    /// Swap interval: on, adaptive or off
+    #[arg(long, value_name = "MODE", default_value = "on")]
+    pub vsync: VsyncMode,
+}
+
+impl GlArgs {
+    /// The APIs to try, in order.
+    pub fn apis(&self) -> Vec<GlApi> {
+        if self.apis.is_empty() {
+            GlApi::FALLBACK_CHAIN.to_vec()
+        } else {
+            self.apis.clone()
+        }
+    }
+
+    /// `requirements` with the sample count from `--msaa`, if given.
+    pub fn config_requirements(&self, requirements: ConfigRequirements) -> ConfigRequirements {
+        ConfigRequirements {
+            samples: self.msaa.unwrap_or(requirements.samples),
+            ..requirements
+        }
+    }
+}
+
+fn parse_size(s: &str) -> Result<[u32; 2], String> {
+    let invalid = || format!("invalid size: {s} (expected WIDTHxHEIGHT, e.g. 1280x720)");
+    let (width, height) = s.split_once('x').ok_or_else(invalid)?;
+    let size = [
+        width.parse().map_err(|_| invalid())?,
+        height.parse().map_err(|_| invalid())?,
+    ];
+    if size.contains(&0) {
+        return Err(format!(
+            "invalid size: {s} (width and height must not be 0)"
+        ));
+    }
+    Ok(size)
+}
+
+fn parse_color(s: &str) -> Result<[f32; 3], String> {
+    let invalid = || format!("invalid color: {s} (expected R,G,B from 0 to 1, or #RRGGBB)");
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 {
+            return Err(invalid());
+        }
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .map(|value| f32::from(value) / 255.0)
+                .ok_or_else(invalid)
+        };
+        return Ok([channel(0)?, channel(2)?, channel(4)?]);
+    }
+
+    let channels: Vec<f32> = s
+        .split(',')
+        .map(|channel| channel.trim().parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    match channels[..] {
+        [r, g, b] if channels.iter().all(|c| (0.0..=1.0).contains(c)) => Ok([r, g, b]),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_msaa(s: &str) -> Result<u8, String> {
+    match s.parse() {
+        Ok(samples @ (0 | 2 | 4 | 8 | 16)) => Ok(samples),
+        _ => Err(format!(
+            "invalid sample count: {s} (expected 0, 2, 4, 8 or 16)"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("1280x720"), Ok([1280, 720]));
+        assert_eq!(parse_size("1x1"), Ok([1, 1]));
+    }
+
+    #[test]
+    fn rejects_zero_sizes() {
+        assert!(parse_size("0x5").is_err());
+        assert!(parse_size("5x0").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_sizes() {
+        for size in [
+            "",
+            "x",
+            "1280",
+            "1280X720",
+            "1280x",
+            "-1x5",
+            "1280x720x3",
+            " 1x1",
+        ] {
+            assert!(parse_size(size).is_err(), "{size:?}");
+        }
+    }
+
+    #[test]
+    fn parses_color_channels() {
+        assert_eq!(parse_color("1,0.5,0"), Ok([1.0, 0.5, 0.0]));
+        assert_eq!(parse_color(" 0 , 0.25,1 "), Ok([0.0, 0.25, 1.0]));
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_color("#ff8000"), Ok([1.0, 128.0 / 255.0, 0.0]));
+        assert_eq!(parse_color("#FF8000"), parse_color("#ff8000"));
+        assert_eq!(parse_color("#000000"), Ok([0.0; 3]));
+    }
+
+    #[test]
+    fn rejects_malformed_colors() {
+        for color in [
+            "",
+            "1,0",
+            "1,0,0,0",
+            "1,2,0",
+            "-0.1,0,0",
+            "nan,0,0",
+            "red",
+            "#ff80",
+            "#ff800000",
+            "#gg0000",
+            "ff8000",
+            // Six bytes, but a character boundary falls inside a channel
+            "#fff\u{e9}f",
+        ] {
+            assert!(parse_color(color).is_err(), "{color:?}");
+        }
+    }
+
+    #[test]
+    fn parses_sample_counts() {
+        for samples in [0, 2, 4, 8, 16] {
+            assert_eq!(parse_msaa(&samples.to_string()), Ok(samples));
+        }
+        for samples in ["1", "3", "32", "-4", "four", ""] {
+            assert!(parse_msaa(samples).is_err(), "{samples:?}");
+        }
+    }
+}
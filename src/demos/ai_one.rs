@@ -0,0 +1,1130 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use glow::*;
+use serde::{Deserialize, Serialize};
+use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::keyboard::{KeyCode, ModifiersState};
+use winit::window::{Window, WindowAttributes, WindowId};
+use winit_test::camera::{OrbitCamera, OrbitControls};
+use winit_test::capture::{self, ScreenshotLayers};
+use winit_test::cli::{CommonArgs, GlArgs};
+use winit_test::frame_stats::{FrameStats, Phase};
+use winit_test::gl_config::ConfigRequirements;
+use winit_test::gl_window::{GlWindow, GlWindowBuilder, VsyncMode};
+use winit_test::gpu_timer::{GpuPass, GpuTimer};
+use winit_test::headless::HeadlessOptions;
+use winit_test::mesh;
+use winit_test::model_viewer::ModelViewer;
+use winit_test::persistence::{StateFile, UiState};
+use winit_test::raster::{self, Target, Vertex};
+use winit_test::recorder::{FrameRecorder, RecordError, RecordOptions};
+use winit_test::redraw::{self, RedrawScheduler};
+use winit_test::scene::{self, Gallery};
+use winit_test::shader_reload::POLL_INTERVAL;
+use winit_test::soft_painter::SoftPainter;
+use winit_test::soft_window::SoftWindow;
+
+/// A gallery of scenes or a model drawn with glow under an egui UI, falling
+/// back to softbuffer when GL is unavailable.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    gl: GlArgs,
+
+    /// Open the color picker at startup
+    #[arg(long)]
+    show_color_picker: bool,
+
+    /// Skip GL and draw with the software renderer
+    #[arg(long)]
+    software: bool,
+
+    /// Redraw continuously instead of when something changed
+    #[arg(long)]
+    continuous: bool,
+
+    /// Record every frame, to a directory of numbered PNGs or a .y4m file
+    #[arg(long, value_name = "PATH")]
+    record: Option<PathBuf>,
+
+    /// Simulated frame rate of the recording
+    #[arg(
+        long,
+        value_name = "FPS",
+        requires = "record",
+        value_parser = clap::value_parser!(u32).range(1..),
+    )]
+    record_fps: Option<u32>,
+
+    /// Show this OBJ or glTF model instead of the triangle
+    #[arg(long, value_name = "PATH")]
+    model: Option<PathBuf>,
+
+    /// Open the scene gallery at startup
+    #[arg(long)]
+    gallery: bool,
+
+    /// Start with this scene from the gallery
+    #[arg(long, value_name = "SCENE", value_parser = parse_scene)]
+    scene: Option<String>,
+}
+
+impl Args {
+    fn record_options(&self) -> Option<RecordOptions> {
+        let mut options = RecordOptions::new(self.record.clone()?);
+        if let Some(fps) = self.record_fps {
+            options.fps = fps;
+        }
+        Some(options)
+    }
+}
+
+fn parse_scene(s: &str) -> Result<String, String> {
+    let ids = scene::default_scene_ids();
+    if ids.contains(&s) {
+        Ok(s.to_owned())
+    } else {
+        Err(format!(
+            "unknown scene: {s} (expected one of {})",
+            ids.join(", ")
+        ))
+    }
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    if let Some(options) = args.common.headless() {
+        return if args.software {
+            if args.record.is_some() {
+                return Err("--record needs GL and does not work with --software".into());
+            }
+            if args.model.is_some() {
+                return Err("--model needs GL and does not work with --software".into());
+            }
+            if args.gallery || args.scene.is_some() {
+                return Err("scenes need GL and do not work with --software".into());
+            }
+            run_headless_software(&options, &args)
+        } else {
+            run_headless(&options, &args)
+        };
+    }
+
+    let event_loop = EventLoop::new()?;
+    let mut app = Application::new(args);
+    event_loop.run_app(&mut app).map_err(Into::into)
+}
+
+/// Render `options.frames` frames offscreen through the same [`Renderer`] the
+/// window uses and save the last one, recording all of them with `--record`.
+#[cfg(not(target_vendor = "apple"))]
+fn run_headless(options: &HeadlessOptions, args: &Args) -> Result<(), Box<dyn Error>> {
+    use winit_test::headless::{HeadlessContext, egui_input};
+
+    let size = options.size;
+    let context = HeadlessContext::with_apis(size[0], size[1], &args.gl.apis())?;
+    let mut renderer = Renderer::new(context.gl.clone(), &context.api.glsl_version_header())?;
+    renderer.set_viewport(size);
+    renderer.color_picker.open = args.show_color_picker;
+    if let Some(color) = args.common.color {
+        renderer.color_picker.color = color;
+    }
+    if let Some(record) = args.record_options() {
+        renderer.recorder = Some(FrameRecorder::new(&record)?);
+    }
+    if let Some(model) = &args.model {
+        renderer.model_viewer.load(model)?;
+    }
+    renderer.gallery.open = args.gallery;
+    if let Some(scene) = &args.scene {
+        renderer.gallery.select_id(scene)?;
+    }
+
+    for frame in 0..options.frames {
+        renderer.render(size, egui_input(frame, size));
+    }
+    renderer.finish_recording()?;
+
+    context.read_pixels().save(&options.output)?;
+    log::info!(
+        "Wrote frame {} to {}",
+        options.frames,
+        options.output.display()
+    );
+    Ok(())
+}
+
+#[cfg(target_vendor = "apple")]
+fn run_headless(_options: &HeadlessOptions, _args: &Args) -> Result<(), Box<dyn Error>> {
+    Err("headless rendering needs EGL, which is not available on this platform".into())
+}
+
+/// `--headless --software`: render through the softbuffer fallback's
+/// [`SoftRenderer`] into a plain buffer, no GL involved.
+fn run_headless_software(options: &HeadlessOptions, args: &Args) -> Result<(), Box<dyn Error>> {
+    use winit_test::headless::egui_input;
+
+    let size = options.size;
+    let mut pixels = vec![0; size[0] as usize * size[1] as usize];
+    let mut renderer = SoftRenderer::new();
+    renderer.camera.set_viewport(size);
+    renderer.color_picker.open = args.show_color_picker;
+    if let Some(color) = args.common.color {
+        renderer.color_picker.color = color;
+    }
+
+    for frame in 0..options.frames {
+        let mut target = Target::new(&mut pixels, size[0], size[1]);
+        renderer.render(&mut target, egui_input(frame, size));
+    }
+
+    raster::to_rgba_image(&pixels, size[0], size[1]).save(&options.output)?;
+    log::info!(
+        "Wrote frame {} to {}",
+        options.frames,
+        options.output.display()
+    );
+    Ok(())
+}
+
+struct Application {
+    windows: HashMap<WindowId, WindowState>,
+    /// Size and fullscreen from the command line.
+    window_attributes: WindowAttributes,
+    /// Context APIs, MSAA and initial swap interval for GL windows.
+    gl: GlArgs,
+    show_color_picker: bool,
+    /// Initial triangle color (`--color`), over the saved one.
+    color: Option<[f32; 3]>,
+    /// Skip GL and go straight to the softbuffer fallback (`--software`).
+    software: bool,
+    /// Redraw every loop iteration instead of on demand (`--continuous`).
+    continuous: bool,
+    /// Record every frame of the window (`--record PATH [--record-fps N]`).
+    record: Option<RecordOptions>,
+    /// Model to show instead of the triangle at startup (`--model PATH`).
+    model: Option<PathBuf>,
+    /// Open the scene gallery at startup (`--gallery`).
+    gallery: bool,
+    /// Scene to start with (`--scene ID`).
+    scene: Option<String>,
+    /// Where the UI state is kept between runs; `None` without a config dir.
+    state_file: Option<StateFile>,
+    /// Loaded at startup and applied to the first window.
+    saved_state: Option<SavedState>,
+    /// When to next check the shader files for edits.
+    next_shader_poll: Instant,
+}
+
+/// What `ai_one` keeps between runs: the color picker and egui's memory,
+/// which holds the egui windows' positions and sizes.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedState {
+    ui: UiState,
+    egui_memory: Option<egui::Memory>,
+}
+
+struct WindowState {
+    backend: Backend,
+    egui_winit: egui_winit::State,
+    redraw: RedrawScheduler,
+    orbit_controls: OrbitControls,
+    /// Tracked for Shift+F12, since key events don't carry modifiers.
+    modifiers: ModifiersState,
+}
+
+/// How a window's frames are drawn and presented.
+///
+/// GL is preferred; when the GL window or renderer cannot be created the
+/// window falls back to drawing the same frame on the CPU into a softbuffer
+/// surface, so broken GL stacks still get a working UI.
+enum Backend {
+    // Fields drop in order: the renderer's GL objects go before the context.
+    // Everything is boxed to keep the two variants of similar size.
+    Gl {
+        renderer: Box<Renderer>,
+        gl_window: Box<GlWindow>,
+    },
+    Software {
+        renderer: Box<SoftRenderer>,
+        soft_window: SoftWindow,
+    },
+}
+
+impl Backend {
+    fn window(&self) -> &Window {
+        match self {
+            Self::Gl { gl_window, .. } => &gl_window.window,
+            Self::Software { soft_window, .. } => &soft_window.window,
+        }
+    }
+
+    fn egui_ctx(&self) -> &egui::Context {
+        match self {
+            Self::Gl { renderer, .. } => &renderer.egui_ctx,
+            Self::Software { renderer, .. } => &renderer.egui_ctx,
+        }
+    }
+
+    fn frame_stats_mut(&mut self) -> &mut FrameStats {
+        match self {
+            Self::Gl { renderer, .. } => &mut renderer.frame_stats,
+            Self::Software { renderer, .. } => &mut renderer.frame_stats,
+        }
+    }
+
+    fn color_picker_mut(&mut self) -> &mut ColorPicker {
+        match self {
+            Self::Gl { renderer, .. } => &mut renderer.color_picker,
+            Self::Software { renderer, .. } => &mut renderer.color_picker,
+        }
+    }
+
+    /// The camera of whatever is shown: the model if one is loaded, else the
+    /// triangle.
+    fn camera_mut(&mut self) -> &mut OrbitCamera {
+        match self {
+            Self::Gl { renderer, .. } => renderer.camera_mut(),
+            Self::Software { renderer, .. } => &mut renderer.camera,
+        }
+    }
+
+    /// Show or hide the "Models" window. Models need GL, so the software
+    /// backend has none.
+    fn toggle_models(&mut self) {
+        match self {
+            Self::Gl { renderer, .. } => {
+                renderer.model_viewer.open = !renderer.model_viewer.open;
+            }
+            Self::Software { .. } => log::info!("Models are only shown with GL"),
+        }
+    }
+
+    /// Show or hide the scene gallery, which like models needs GL.
+    fn toggle_gallery(&mut self) {
+        match self {
+            Self::Gl { renderer, .. } => {
+                renderer.gallery.open = !renderer.gallery.open;
+            }
+            Self::Software { .. } => log::info!("Scenes are only shown with GL"),
+        }
+    }
+
+    /// Pick up shader edits, returning `true` when the window needs redrawing.
+    /// The software backend has no shaders.
+    fn poll_shaders(&mut self) -> bool {
+        match self {
+            Self::Gl { renderer, .. } => renderer.poll_shaders(),
+            Self::Software { .. } => false,
+        }
+    }
+
+    /// Save a screenshot of the next frame drawn.
+    fn request_screenshot(&mut self, layers: ScreenshotLayers) {
+        match self {
+            Self::Gl { renderer, .. } => renderer.screenshot = Some(layers),
+            Self::Software { renderer, .. } => renderer.screenshot = Some(layers),
+        }
+    }
+
+    /// Returns `true` when the window has a non-zero size and should be redrawn.
+    fn resize(&mut self, size: PhysicalSize<u32>) -> bool {
+        let viewport = [size.width, size.height];
+        match self {
+            Self::Gl {
+                renderer,
+                gl_window,
+            } => {
+                renderer.set_viewport(viewport);
+                gl_window.resize(size)
+            }
+            // The softbuffer surface is resized when the next frame is presented
+            Self::Software { renderer, .. } => {
+                renderer.camera.set_viewport(viewport);
+                size.width > 0 && size.height > 0
+            }
+        }
+    }
+
+    /// Draw and present a frame, returning egui's platform output and repaint
+    /// delay unless nothing could be drawn.
+    fn render(&mut self, raw_input: egui::RawInput) -> Option<(egui::PlatformOutput, Duration)> {
+        match self {
+            Self::Gl {
+                renderer,
+                gl_window,
+            } => {
+                let size = gl_window.window.inner_size();
+                let output = renderer.render([size.width, size.height], raw_input);
+                let start = Instant::now();
+                if let Err(err) = gl_window.swap_buffers() {
+                    log::error!("Failed to swap buffers: {err}");
+                }
+                renderer.frame_stats.record(Phase::Swap, start.elapsed());
+                if let Some(vsync) = &mut renderer.vsync {
+                    vsync.update(gl_window);
+                }
+                Some(output)
+            }
+            Self::Software {
+                renderer,
+                soft_window,
+            } => {
+                // Presenting is whatever `present` spends outside of drawing
+                let start = Instant::now();
+                let mut draw_time = Duration::ZERO;
+                let result = soft_window.present(|target| {
+                    let draw_start = Instant::now();
+                    let output = renderer.render(target, raw_input);
+                    draw_time = draw_start.elapsed();
+                    output
+                });
+                renderer
+                    .frame_stats
+                    .record(Phase::Swap, start.elapsed().saturating_sub(draw_time));
+
+                match result {
+                    Ok(output) => output,
+                    Err(err) => {
+                        log::error!("Failed to present frame: {err}");
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The color picker window and the triangle color it edits, shared by both
+/// backends.
+struct ColorPicker {
+    open: bool,
+    color: [f32; 3],
+}
+
+impl Default for ColorPicker {
+    fn default() -> Self {
+        Self::from_state(&UiState::default())
+    }
+}
+
+impl ColorPicker {
+    fn from_state(state: &UiState) -> Self {
+        Self {
+            open: state.show_color_picker,
+            color: state.color,
+        }
+    }
+
+    fn state(&self) -> UiState {
+        UiState {
+            color: self.color,
+            show_color_picker: self.open,
+        }
+    }
+
+    /// `vsync` is `None` where there is no swap interval to control.
+    fn show(&mut self, ctx: &egui::Context, vsync: Option<&mut Vsync>) {
+        if !self.open {
+            return;
+        }
+
+        let color = &mut self.color;
+        egui::Window::new("Color Picker")
+            .default_size([300.0, 200.0])
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                ui.heading("Triangle Color");
+                ui.separator();
+
+                ui.label("Red:");
+                ui.add(egui::Slider::new(&mut color[0], 0.0..=1.0));
+
+                ui.label("Green:");
+                ui.add(egui::Slider::new(&mut color[1], 0.0..=1.0));
+
+                ui.label("Blue:");
+                ui.add(egui::Slider::new(&mut color[2], 0.0..=1.0));
+
+                ui.separator();
+                egui::CollapsingHeader::new("Presentation").show(ui, |ui| match vsync {
+                    Some(vsync) => vsync.ui(ui),
+                    None => {
+                        ui.label("Swap interval is not available on this backend");
+                    }
+                });
+
+                ui.separator();
+                ui.label("Press SPACE to toggle this window, F3 for frame timing");
+                ui.label(
+                    "Drag to orbit, right-drag to pan, scroll to zoom, P to switch projection",
+                );
+            });
+    }
+}
+
+/// The swap interval picked in the UI and the one the driver accepted.
+struct Vsync {
+    requested: VsyncMode,
+    /// The last request passed to [`GlWindow::set_vsync`].
+    applied: VsyncMode,
+    /// `None` when no mode could be set and the driver default applies.
+    active: Option<VsyncMode>,
+}
+
+impl Vsync {
+    fn new(gl_window: &GlWindow, requested: VsyncMode) -> Self {
+        Self {
+            requested,
+            applied: requested,
+            active: gl_window.set_vsync(requested),
+        }
+    }
+
+    /// Apply a mode picked in the UI since the last call.
+    fn update(&mut self, gl_window: &GlWindow) {
+        if self.requested != self.applied {
+            self.applied = self.requested;
+            self.active = gl_window.set_vsync(self.requested);
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            for mode in VsyncMode::ALL {
+                ui.radio_value(&mut self.requested, mode, mode.to_string());
+            }
+        });
+        match self.active {
+            Some(mode) => ui.label(format!("Active: {mode}")),
+            None => ui.label("Active: driver default"),
+        };
+    }
+}
+
+/// Everything needed to draw a frame: the gallery's scene or a model, the
+/// egui UI and its state.
+///
+/// Independent of where the frame ends up, so the window and `--headless`
+/// share the exact same drawing code.
+struct Renderer {
+    gl: Arc<glow::Context>,
+    /// The scenes to pick from, the colored triangle first.
+    gallery: Gallery,
+    camera: OrbitCamera,
+    /// Replaces the gallery's scene while a model is picked.
+    model_viewer: ModelViewer,
+
+    egui_ctx: egui::Context,
+    egui_painter: egui_glow::Painter,
+
+    color_picker: ColorPicker,
+    /// Set once the renderer draws to a window; offscreen there is no swap interval.
+    vsync: Option<Vsync>,
+    frame_stats: FrameStats,
+    gpu_timer: GpuTimer,
+    /// Read back during the next frame, right after the requested layers are drawn.
+    screenshot: Option<ScreenshotLayers>,
+    /// Set while recording; also drives egui with simulated time.
+    recorder: Option<FrameRecorder>,
+}
+
+impl Renderer {
+    fn new(gl: Arc<glow::Context>, glsl_version_header: &str) -> Result<Self, Box<dyn Error>> {
+        // Scenes create their GL resources when picked. Their shaders live in
+        // `shaders/` and are recompiled whenever they are edited.
+        let gallery = Gallery::new(gl.clone(), glsl_version_header)?;
+        let model_viewer = ModelViewer::new(gl.clone(), glsl_version_header)?;
+
+        // Create egui painter for rendering egui with glow
+        let egui_painter = egui_glow::Painter::new(gl.clone(), "", None, false)?;
+        let gpu_timer = GpuTimer::new(gl.clone());
+
+        Ok(Self {
+            gl,
+            gallery,
+            camera: OrbitCamera::default(),
+            model_viewer,
+            egui_ctx: egui::Context::default(),
+            egui_painter,
+            color_picker: ColorPicker::default(),
+            vsync: None,
+            frame_stats: FrameStats::new(),
+            gpu_timer,
+            screenshot: None,
+            recorder: None,
+        })
+    }
+
+    /// Draw one frame into the current framebuffer and return egui's platform
+    /// output (cursor changes, clipboard, etc.) for the caller to handle,
+    /// together with how soon the next frame is wanted.
+    fn render(
+        &mut self,
+        [width, height]: [u32; 2],
+        mut raw_input: egui::RawInput,
+    ) -> (egui::PlatformOutput, Duration) {
+        // Recordings advance by exactly one timestep per frame, however long
+        // rendering and writing the frames takes
+        if let Some(recorder) = &self.recorder {
+            raw_input.time = Some(recorder.time());
+            raw_input.predicted_dt = recorder.timestep().as_secs_f32();
+        }
+
+        let gl = &self.gl;
+        self.frame_stats.begin_frame();
+        // GPU times arrive a few frames late; they are shown with this frame
+        self.frame_stats.record_gpu(self.gpu_timer.begin_frame());
+
+        let start = Instant::now();
+        self.gpu_timer.begin(GpuPass::Scene);
+        unsafe {
+            gl.viewport(0, 0, width as i32, height as i32);
+            gl.clear_color(0.1, 0.2, 0.3, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+        }
+        if self.model_viewer.is_active() {
+            self.model_viewer.draw();
+        } else {
+            self.gallery.render(self.color_picker.color, &self.camera);
+        }
+        self.gpu_timer.end();
+        self.frame_stats.record(Phase::Draw, start.elapsed());
+
+        if self
+            .screenshot
+            .take_if(|layers| *layers == ScreenshotLayers::Scene)
+            .is_some()
+        {
+            save_screenshot(capture::read_framebuffer(gl, [width, height]));
+        }
+
+        let color_picker = &mut self.color_picker;
+        let mut vsync = self.vsync.as_mut();
+        let gallery = &mut self.gallery;
+        let model_viewer = &mut self.model_viewer;
+        let frame_stats = &mut self.frame_stats;
+
+        // Run egui UI code
+        let start = Instant::now();
+        let full_output = self.egui_ctx.run(raw_input, |ctx| {
+            // The side panel goes first so windows stay clear of it
+            gallery.show(ctx);
+            if !model_viewer.is_active() {
+                gallery.update(ctx);
+            }
+            model_viewer.show(ctx);
+            color_picker.show(ctx, vsync.as_deref_mut());
+            frame_stats.show(ctx);
+        });
+        self.frame_stats.record(Phase::Ui, start.elapsed());
+
+        let repaint_delay = redraw::repaint_delay(&full_output);
+
+        // Tessellate egui's shapes into triangles for rendering
+        let start = Instant::now();
+        let clipped_primitives = self
+            .egui_ctx
+            .tessellate(full_output.shapes, full_output.pixels_per_point);
+        self.frame_stats.record(Phase::Tessellate, start.elapsed());
+
+        // CRITICAL: Handle texture updates from egui
+        // Issue: Initially we got "Failed to find texture Managed(0)" warnings
+        // because we weren't uploading egui's font atlas and other textures to the GPU.
+        // egui generates texture deltas (new textures or updates) that must be uploaded
+        // before rendering, otherwise egui can't render text or images.
+        let start = Instant::now();
+        for (id, image_delta) in &full_output.textures_delta.set {
+            self.egui_painter.set_texture(*id, image_delta);
+        }
+
+        // Render egui on top of our OpenGL content
+        self.gpu_timer.begin(GpuPass::Egui);
+        self.egui_painter.paint_primitives(
+            [width, height],
+            full_output.pixels_per_point,
+            &clipped_primitives,
+        );
+        self.gpu_timer.end();
+
+        // Free textures that are no longer needed
+        for id in &full_output.textures_delta.free {
+            self.egui_painter.free_texture(*id);
+        }
+        self.frame_stats.record(Phase::Paint, start.elapsed());
+
+        // Read back before the caller swaps buffers, after which the back
+        // buffer's contents are undefined
+        if self.screenshot.take().is_some() {
+            save_screenshot(capture::read_framebuffer(&self.gl, [width, height]));
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.capture(&self.gl, [width, height]);
+        }
+
+        (full_output.platform_output, repaint_delay)
+    }
+}
+
+impl Renderer {
+    /// Pick up shader edits, returning `true` when the frame needs redrawing.
+    fn poll_shaders(&mut self) -> bool {
+        // Both are polled, so an edit is noticed whichever is on screen
+        self.gallery.poll_shaders() | self.model_viewer.poll_shaders()
+    }
+
+    /// Keep both cameras' projections in step with the framebuffer's size.
+    fn set_viewport(&mut self, size: [u32; 2]) {
+        self.camera.set_viewport(size);
+        self.model_viewer.camera.set_viewport(size);
+    }
+
+    fn camera_mut(&mut self) -> &mut OrbitCamera {
+        if self.model_viewer.is_active() {
+            &mut self.model_viewer.camera
+        } else {
+            &mut self.camera
+        }
+    }
+
+    /// Write out the frames still being read back and close the recording.
+    fn finish_recording(&mut self) -> Result<(), RecordError> {
+        if let Some(mut recorder) = self.recorder.take() {
+            let frames = recorder.finish(&self.gl)?;
+            log::info!("Recorded {frames} frames");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        if let Err(err) = self.finish_recording() {
+            log::error!("Failed to finish recording: {err}");
+        }
+        self.egui_painter.destroy();
+    }
+}
+
+/// CPU counterpart of [`Renderer`] for the softbuffer fallback: the same
+/// triangle and UI, drawn with the software rasterizer and egui painter.
+struct SoftRenderer {
+    egui_ctx: egui::Context,
+    egui_painter: SoftPainter,
+
+    camera: OrbitCamera,
+    color_picker: ColorPicker,
+    frame_stats: FrameStats,
+    screenshot: Option<ScreenshotLayers>,
+}
+
+impl SoftRenderer {
+    fn new() -> Self {
+        Self {
+            egui_ctx: egui::Context::default(),
+            egui_painter: SoftPainter::new(),
+            camera: OrbitCamera::default(),
+            color_picker: ColorPicker::default(),
+            frame_stats: FrameStats::new(),
+            screenshot: None,
+        }
+    }
+
+    fn render(
+        &mut self,
+        target: &mut Target,
+        raw_input: egui::RawInput,
+    ) -> (egui::PlatformOutput, Duration) {
+        self.frame_stats.begin_frame();
+
+        let start = Instant::now();
+        let (width, height) = (target.width, target.height);
+        let color = self.color_picker.color;
+        // The same mesh the GL renderer draws, with the picked color
+        let view_projection = self.camera.view_projection();
+        let vertices = mesh::TRIANGLE.map(|vertex| {
+            let [x, y, _] = view_projection.project_point(vertex.position);
+            Vertex {
+                position: raster::ndc_to_pixel([x, y], width, height),
+                color,
+            }
+        });
+        target.clear([0.1, 0.2, 0.3]);
+        target.fill_triangle(&vertices, None);
+        self.frame_stats.record(Phase::Draw, start.elapsed());
+
+        if self
+            .screenshot
+            .take_if(|layers| *layers == ScreenshotLayers::Scene)
+            .is_some()
+        {
+            save_screenshot(raster::to_rgba_image(target.pixels, width, height));
+        }
+
+        let color_picker = &mut self.color_picker;
+        let frame_stats = &mut self.frame_stats;
+        let start = Instant::now();
+        let full_output = self.egui_ctx.run(raw_input, |ctx| {
+            color_picker.show(ctx, None);
+            frame_stats.show(ctx);
+        });
+        self.frame_stats.record(Phase::Ui, start.elapsed());
+        let repaint_delay = redraw::repaint_delay(&full_output);
+
+        let start = Instant::now();
+        let clipped_primitives = self
+            .egui_ctx
+            .tessellate(full_output.shapes, full_output.pixels_per_point);
+        self.frame_stats.record(Phase::Tessellate, start.elapsed());
+
+        let start = Instant::now();
+        self.egui_painter.paint_and_update_textures(
+            target,
+            full_output.pixels_per_point,
+            &clipped_primitives,
+            &full_output.textures_delta,
+        );
+        self.frame_stats.record(Phase::Paint, start.elapsed());
+
+        if self.screenshot.take().is_some() {
+            save_screenshot(raster::to_rgba_image(target.pixels, width, height));
+        }
+
+        (full_output.platform_output, repaint_delay)
+    }
+}
+
+fn save_screenshot(image: image::RgbaImage) {
+    match capture::save_screenshot(image) {
+        Ok(path) => log::info!("Saved screenshot to {}", path.display()),
+        Err(err) => log::error!("Failed to save screenshot: {err}"),
+    }
+}
+
+impl Application {
+    fn new(args: Args) -> Self {
+        let state_file = StateFile::new("ai_one");
+        let saved_state = state_file.as_ref().and_then(StateFile::load);
+        Self {
+            windows: HashMap::new(),
+            window_attributes: args.common.window_attributes(),
+            record: args.record_options(),
+            gl: args.gl,
+            show_color_picker: args.show_color_picker,
+            color: args.common.color,
+            software: args.software,
+            continuous: args.continuous,
+            model: args.model,
+            gallery: args.gallery,
+            scene: args.scene,
+            state_file,
+            saved_state,
+            next_shader_poll: Instant::now(),
+        }
+    }
+
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Box<dyn Error>> {
+        let window_attributes = self
+            .window_attributes
+            .clone()
+            .with_title("Glow OpenGL Window with egui - Press SPACE for color picker");
+
+        let gl_backend = if self.software {
+            None
+        } else {
+            Self::create_gl_backend(
+                event_loop,
+                window_attributes.clone(),
+                &self.gl,
+                self.record.as_ref(),
+                self.model.as_deref(),
+                self.scene.as_deref(),
+            )
+            .inspect_err(|err| log::warn!("GL is unavailable, falling back to softbuffer: {err}"))
+            .ok()
+        };
+        let backend = match gl_backend {
+            Some(backend) => backend,
+            None => {
+                if self.record.is_some() {
+                    log::warn!("Recording needs GL, this window will not be recorded");
+                }
+                if self.model.is_some() {
+                    log::warn!("Models need GL, showing the triangle instead");
+                }
+                if self.gallery || self.scene.is_some() {
+                    log::warn!("Scenes need GL, showing the triangle instead");
+                }
+                let soft_window = SoftWindow::new(
+                    event_loop,
+                    window_attributes
+                        .with_title("Softbuffer Window with egui - Press SPACE for color picker"),
+                )?;
+                Backend::Software {
+                    renderer: Box::new(SoftRenderer::new()),
+                    soft_window,
+                }
+            }
+        };
+        self.add_window(backend);
+        Ok(())
+    }
+
+    fn create_gl_backend(
+        event_loop: &ActiveEventLoop,
+        window_attributes: WindowAttributes,
+        gl: &GlArgs,
+        record: Option<&RecordOptions>,
+        model: Option<&Path>,
+        scene: Option<&str>,
+    ) -> Result<Backend, Box<dyn Error>> {
+        // Models need a depth buffer, which the default framebuffer config skips
+        let gl_window = GlWindowBuilder::new(window_attributes)
+            .with_config_requirements(gl.config_requirements(ConfigRequirements {
+                depth_bits: 24,
+                ..Default::default()
+            }))
+            .with_apis(gl.apis())
+            .build(event_loop)?;
+        let mut renderer =
+            Renderer::new(gl_window.gl.clone(), &gl_window.api.glsl_version_header())?;
+        renderer.vsync = Some(Vsync::new(&gl_window, gl.vsync));
+        if let Some(record) = record {
+            renderer.recorder = Some(FrameRecorder::new(record)?);
+        }
+        // A model that fails to load leaves the triangle, not a missing window
+        if let Some(model) = model
+            && let Err(err) = renderer.model_viewer.load(model)
+        {
+            log::error!("Failed to load {}: {err}", model.display());
+        }
+        // Likewise a scene that fails to set up leaves the first one
+        if let Some(scene) = scene
+            && let Err(err) = renderer.gallery.select_id(scene)
+        {
+            log::error!("Failed to show scene {scene}: {err}");
+        }
+        Ok(Backend::Gl {
+            renderer: Box::new(renderer),
+            gl_window: Box::new(gl_window),
+        })
+    }
+
+    fn add_window(&mut self, mut backend: Backend) {
+        if let Some(saved) = self.saved_state.take() {
+            *backend.color_picker_mut() = ColorPicker::from_state(&saved.ui);
+            if let Some(memory) = saved.egui_memory {
+                backend.egui_ctx().memory_mut(|m| *m = memory);
+            }
+        }
+        // Command line options win over the saved state
+        let color_picker = backend.color_picker_mut();
+        color_picker.open |= self.show_color_picker;
+        if let Some(color) = self.color {
+            color_picker.color = color;
+        }
+        if let Backend::Gl { renderer, .. } = &mut backend {
+            renderer.gallery.open = self.gallery;
+        }
+        backend.resize(backend.window().inner_size());
+
+        // A recording wants every frame, not just the ones egui asks for
+        let recording =
+            matches!(&backend, Backend::Gl { renderer, .. } if renderer.recorder.is_some());
+        let window = backend.window();
+        let window_id = window.id();
+
+        // Initialize egui-winit state, feeding window events into the renderer's egui context
+        let egui_winit = egui_winit::State::new(
+            backend.egui_ctx().clone(),
+            egui::ViewportId::ROOT,
+            window,
+            Some(window.scale_factor() as f32),
+            None,
+            None,
+        );
+
+        // Request focus for the window to ensure keyboard events are received
+        window.focus_window();
+
+        self.windows.insert(
+            window_id,
+            WindowState {
+                backend,
+                egui_winit,
+                redraw: RedrawScheduler::new(self.continuous || recording),
+                orbit_controls: OrbitControls::new(),
+                modifiers: ModifiersState::default(),
+            },
+        );
+    }
+
+    /// Write `window_state`'s color picker and egui memory to the state file.
+    fn save_state(state_file: Option<&mut StateFile>, window_state: &mut WindowState) {
+        let Some(state_file) = state_file else {
+            return;
+        };
+        let backend = &mut window_state.backend;
+        let egui_memory = backend.egui_ctx().memory(|m| m.clone());
+        state_file.save(&SavedState {
+            ui: backend.color_picker_mut().state(),
+            egui_memory: Some(egui_memory),
+        });
+    }
+}
+
+impl ApplicationHandler for Application {
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let window_state = match self.windows.get_mut(&window_id) {
+            Some(window) => window,
+            None => return,
+        };
+
+        // IMPORTANT: Handle keyboard input BEFORE passing to egui
+        // This allows us to intercept keys for application-level shortcuts
+        // Issue: Initially keyboard events weren't being received because we weren't
+        // checking for them explicitly and the window might not have had focus
+        if let WindowEvent::KeyboardInput { event, .. } = &event
+            && event.state == winit::event::ElementState::Pressed
+            && let winit::keyboard::PhysicalKey::Code(key_code) = event.physical_key
+        {
+            match key_code {
+                KeyCode::Space => {
+                    let color_picker = window_state.backend.color_picker_mut();
+                    color_picker.open = !color_picker.open;
+                }
+                KeyCode::F3 => {
+                    let frame_stats = window_state.backend.frame_stats_mut();
+                    frame_stats.visible = !frame_stats.visible;
+                }
+                KeyCode::KeyM => window_state.backend.toggle_models(),
+                KeyCode::KeyG => window_state.backend.toggle_gallery(),
+                KeyCode::KeyP => {
+                    let camera = window_state.backend.camera_mut();
+                    camera.projection = camera.projection.toggled();
+                }
+                // F12 saves the frame as shown, Shift+F12 without the egui layer
+                KeyCode::F12 => {
+                    let layers = if window_state.modifiers.shift_key() {
+                        ScreenshotLayers::Scene
+                    } else {
+                        ScreenshotLayers::SceneAndUi
+                    };
+                    window_state.backend.request_screenshot(layers);
+                }
+                _ => {}
+            }
+            window_state.backend.window().request_redraw();
+        }
+
+        // Pass event to egui for UI interaction
+        let event_response = window_state
+            .egui_winit
+            .on_window_event(window_state.backend.window(), &event);
+        if event_response.repaint {
+            window_state.backend.window().request_redraw();
+        }
+
+        // Mouse input egui doesn't claim moves the camera
+        let egui_wants_pointer = window_state.backend.egui_ctx().wants_pointer_input();
+        if window_state.orbit_controls.on_window_event(
+            window_state.backend.camera_mut(),
+            &event,
+            egui_wants_pointer,
+        ) {
+            window_state.backend.window().request_redraw();
+        }
+
+        match event {
+            WindowEvent::CloseRequested => {
+                Self::save_state(self.state_file.as_mut(), window_state);
+                self.windows.remove(&window_id);
+                if self.windows.is_empty() {
+                    event_loop.exit();
+                }
+            }
+            WindowEvent::RedrawRequested => {
+                // Prepare egui frame
+                let raw_input = window_state
+                    .egui_winit
+                    .take_egui_input(window_state.backend.window());
+
+                // Draw and present through whichever backend the window has
+                if let Some((platform_output, repaint_delay)) =
+                    window_state.backend.render(raw_input)
+                {
+                    // Handle platform-specific output (cursor changes, clipboard, etc.)
+                    window_state
+                        .egui_winit
+                        .handle_platform_output(window_state.backend.window(), platform_output);
+                    window_state.redraw.frame_drawn(repaint_delay);
+                }
+            }
+            WindowEvent::Resized(size) if window_state.backend.resize(size) => {
+                window_state.backend.window().request_redraw();
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                window_state.modifiers = modifiers.state();
+            }
+            _ => {}
+        }
+    }
+
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.windows.is_empty()
+            && let Err(err) = self.create_window(event_loop)
+        {
+            log::error!("Failed to create window: {err}");
+            event_loop.exit();
+        }
+    }
+
+    // Redraw windows whose egui repaint delay has run out, then sleep until the next one
+    // Issue: Requesting a redraw on every iteration kept the UI responsive but
+    // pegged a CPU core even when nothing on screen changed
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        // Save now and then, so a crash or kill doesn't lose everything
+        if let Some(state_file) = &mut self.state_file
+            && state_file.autosave_due(now)
+            && let Some(window_state) = self.windows.values_mut().next()
+        {
+            Self::save_state(Some(state_file), window_state);
+        }
+
+        // Checking the shader files costs a wakeup, but only an edit costs a frame
+        let mut watching_shaders = false;
+        for window_state in self.windows.values_mut() {
+            if let Backend::Gl { .. } = window_state.backend {
+                watching_shaders = true;
+                if now >= self.next_shader_poll && window_state.backend.poll_shaders() {
+                    window_state.backend.window().request_redraw();
+                }
+            }
+        }
+        if now >= self.next_shader_poll {
+            self.next_shader_poll = now + POLL_INTERVAL;
+        }
+
+        let deadlines = self.windows.values_mut().filter_map(|window_state| {
+            window_state.redraw.poll(window_state.backend.window(), now)
+        });
+        let shader_poll = watching_shaders.then_some(self.next_shader_poll);
+        redraw::set_control_flow(event_loop, deadlines.chain(shader_poll));
+    }
+}
@@ -0,0 +1,538 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use glow::HasContext;
+use winit_test::camera::OrbitCamera;
+use winit_test::capture::{self, ScreenshotLayers};
+use winit_test::cli::{CommonArgs, GlArgs};
+use winit_test::frame_stats::{FrameStats, Phase};
+use winit_test::gl_window::{GlApi, VsyncMode};
+use winit_test::headless::HeadlessOptions;
+use winit_test::mesh::{self, Mesh};
+use winit_test::model_viewer::ModelViewer;
+use winit_test::persistence::UiState;
+use winit_test::render_target::RenderTarget;
+use winit_test::shader::ShaderStage;
+use winit_test::shader_reload::{ReloadableProgram, ShaderWatcher, shader_path};
+
+/// The triangle, or a model, rendered into a texture shown in an eframe UI.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    gl: GlArgs,
+
+    /// Open the color picker at startup
+    #[arg(long)]
+    show_color_picker: bool,
+
+    /// Repaint every frame instead of when something changed
+    #[arg(long)]
+    continuous: bool,
+
+    /// Show this OBJ or glTF model instead of the triangle
+    #[arg(long, value_name = "PATH")]
+    model: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    if let Some(options) = args.common.headless() {
+        return run_headless(&options, &args);
+    }
+    // eframe creates the context itself and has no say in which API it gets
+    if !args.gl.apis.is_empty() {
+        return Err("--gl only works with --headless; eframe picks its own GL context".into());
+    }
+
+    let [width, height] = args.common.size;
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([width as f32, height as f32])
+            .with_fullscreen(args.common.fullscreen)
+            .with_title("eframe with Custom OpenGL Rendering"),
+        renderer: eframe::Renderer::Glow,
+        multisampling: args.gl.msaa.unwrap_or(0).into(),
+        // eframe only knows on and off, so adaptive asks for plain vsync
+        vsync: args.gl.vsync != VsyncMode::Immediate,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "eframe glow app",
+        options,
+        Box::new(move |cc| {
+            let mut app = MyApp::new(cc)?;
+            // Command line options win over the saved state
+            app.show_color_picker |= args.show_color_picker;
+            if let Some(color) = args.common.color {
+                app.color = color;
+            }
+            app.continuous = args.continuous;
+            // A model that fails to load leaves the triangle, not a missing window
+            if let Some(model) = &args.model
+                && let Err(err) = app.model_viewer.load(model)
+            {
+                log::error!("Failed to load {}: {err}", model.display());
+            }
+            Ok(Box::new(app))
+        }),
+    )
+    .map_err(Into::into)
+}
+
+/// Run the app's UI without eframe: egui_glow paints it, scene texture
+/// included, into an offscreen framebuffer and the last frame is saved.
+#[cfg(not(target_vendor = "apple"))]
+fn run_headless(options: &HeadlessOptions, args: &Args) -> Result<(), Box<dyn Error>> {
+    use winit_test::headless::{HeadlessContext, egui_input};
+
+    let size = options.size;
+    let context = HeadlessContext::with_apis(size[0], size[1], &args.gl.apis())?;
+    let mut app = MyApp::with_gl(&context.gl)?;
+    app.show_color_picker = args.show_color_picker;
+    if let Some(color) = args.common.color {
+        app.color = color;
+    }
+    if let Some(model) = &args.model {
+        app.model_viewer.load(model)?;
+    }
+
+    let egui_ctx = egui::Context::default();
+    let mut painter = egui_glow::Painter::new(context.gl.clone(), "", None, false)?;
+    app.scene_texture = Some(painter.register_native_texture(app.scene.texture()));
+
+    for frame in 0..options.frames {
+        let full_output = egui_ctx.run(egui_input(frame, size), |ctx| app.ui(ctx));
+        let clipped_primitives =
+            egui_ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
+
+        // Mirror what eframe does each frame: clear, then paint
+        painter.clear(
+            size,
+            eframe::App::clear_color(&app, &egui_ctx.style().visuals),
+        );
+        painter.paint_and_update_textures(
+            size,
+            full_output.pixels_per_point,
+            &clipped_primitives,
+            &full_output.textures_delta,
+        );
+    }
+    // The painter deletes the scene texture, so the app has to go first
+    drop(app);
+    painter.destroy();
+
+    context.read_pixels().save(&options.output)?;
+    log::info!(
+        "Wrote frame {} to {}",
+        options.frames,
+        options.output.display()
+    );
+    Ok(())
+}
+
+#[cfg(target_vendor = "apple")]
+fn run_headless(_options: &HeadlessOptions, _args: &Args) -> Result<(), Box<dyn Error>> {
+    Err("headless rendering needs EGL, which is not available on this platform".into())
+}
+
+struct MyApp {
+    triangle_renderer: TriangleRenderer,
+    camera: OrbitCamera,
+    /// Replaces the triangle while a model is picked.
+    model_viewer: ModelViewer,
+    /// The scene is rendered into this texture and shown as an egui image.
+    scene: RenderTarget,
+    /// `scene`'s texture as registered with egui, once the painter is available.
+    scene_texture: Option<egui::TextureId>,
+
+    // UI state
+    show_color_picker: bool,
+    color: [f32; 3],
+    /// Show the scene in a movable, resizable window instead of the central panel.
+    scene_in_window: bool,
+    /// Scene resolution relative to the size it is displayed at.
+    scene_scale: f32,
+
+    /// Repaint every frame instead of when eframe sees a reason to (`--continuous`).
+    continuous: bool,
+    frame_stats: FrameStats,
+    /// How long rendering the scene took during the last `ui`.
+    draw_time: Duration,
+
+    /// Whether the screenshot button keeps the egui windows in the picture.
+    screenshot_ui: bool,
+    /// Requested this frame, sent as a viewport command at the start of the next.
+    screenshot: Option<ScreenshotLayers>,
+    /// Wakes eframe when a shader file is edited; `None` without eframe.
+    shader_watcher: Option<ShaderWatcher>,
+}
+
+struct TriangleRenderer {
+    program: ReloadableProgram,
+    triangle: Mesh,
+}
+
+impl TriangleRenderer {
+    fn new(gl: &Arc<glow::Context>) -> Result<Self, String> {
+        let triangle = Mesh::new(gl.clone(), &mesh::TRIANGLE, &[0, 1, 2])?;
+
+        // Shaders are loaded from `shaders/` and recompiled when edited
+        let program = ReloadableProgram::load_required(
+            gl.clone(),
+            &GlApi::from_context(gl).glsl_version_header(),
+            &[
+                (ShaderStage::Vertex, &shader_path("triangle.vert")),
+                (ShaderStage::Fragment, &shader_path("triangle.frag")),
+            ],
+        )?;
+
+        Ok(Self { program, triangle })
+    }
+
+    fn paint(&mut self, gl: &glow::Context, color: [f32; 3], camera: &OrbitCamera) {
+        let Some(program) = self.program.program() else {
+            return;
+        };
+
+        program.use_program();
+        let color_location = program.uniform_location("u_color");
+        unsafe {
+            gl.uniform_3_f32(color_location.as_ref(), color[0], color[1], color[2]);
+            gl.uniform_matrix_4_f32_slice(
+                program.uniform_location("u_view_projection").as_ref(),
+                false,
+                camera.view_projection().as_slice(),
+            );
+        }
+        self.triangle.draw();
+    }
+}
+
+impl MyApp {
+    /// Restores the UI state saved by [`eframe::App::save`]; eframe restores
+    /// the egui window positions itself.
+    fn new(cc: &eframe::CreationContext<'_>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let gl = cc.gl.as_ref().ok_or("Failed to get glow context")?;
+        let mut app = Self::with_gl(gl)?;
+        if let Some(state) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<UiState>(storage, eframe::APP_KEY))
+        {
+            app.color = state.color;
+            app.show_color_picker = state.show_color_picker;
+        }
+
+        let paths = app
+            .triangle_renderer
+            .program
+            .paths()
+            .chain(app.model_viewer.shader_paths())
+            .map(Path::to_owned)
+            .collect();
+        let ctx = cc.egui_ctx.clone();
+        app.shader_watcher = Some(ShaderWatcher::spawn(paths, move || ctx.request_repaint()));
+        Ok(app)
+    }
+
+    /// `scene_texture` is left for the caller to register with its painter.
+    fn with_gl(gl: &Arc<glow::Context>) -> Result<Self, String> {
+        let state = UiState::default();
+        Ok(Self {
+            triangle_renderer: TriangleRenderer::new(gl)?,
+            camera: OrbitCamera::default(),
+            model_viewer: ModelViewer::new(
+                gl.clone(),
+                &GlApi::from_context(gl).glsl_version_header(),
+            )?,
+            scene: RenderTarget::new(gl.clone(), [1, 1])?,
+            scene_texture: None,
+            show_color_picker: state.show_color_picker,
+            color: state.color,
+            scene_in_window: false,
+            scene_scale: 1.0,
+            continuous: false,
+            frame_stats: FrameStats::new(),
+            draw_time: Duration::ZERO,
+            screenshot_ui: true,
+            screenshot: None,
+            shader_watcher: None,
+        })
+    }
+
+    /// The whole UI, kept out of `update` so `--headless` can run it without eframe.
+    fn ui(&mut self, ctx: &egui::Context) {
+        // Handle keyboard input
+        ctx.input(|i| {
+            if i.key_pressed(egui::Key::Space) {
+                self.show_color_picker = !self.show_color_picker;
+            }
+            if i.key_pressed(egui::Key::F3) {
+                self.frame_stats.visible = !self.frame_stats.visible;
+            }
+            if i.key_pressed(egui::Key::M) {
+                self.model_viewer.open = !self.model_viewer.open;
+            }
+            if i.key_pressed(egui::Key::P) {
+                let camera = self.camera_mut();
+                camera.projection = camera.projection.toggled();
+            }
+            // F12 saves the frame as shown, Shift+F12 only the scene texture
+            if i.key_pressed(egui::Key::F12) {
+                self.screenshot = Some(if i.modifiers.shift {
+                    ScreenshotLayers::Scene
+                } else {
+                    ScreenshotLayers::SceneAndUi
+                });
+            }
+
+            // eframe answers a screenshot command with an event on the next frame
+            for event in &i.raw.events {
+                if let egui::Event::Screenshot { image, .. } = event {
+                    save_color_image(image);
+                }
+            }
+        });
+
+        // The scene alone is read straight from its texture when it is drawn
+        // below; the full frame is read back by eframe once it is painted
+        let mut scene_screenshot = false;
+        match self.screenshot.take() {
+            Some(ScreenshotLayers::Scene) => scene_screenshot = true,
+            Some(ScreenshotLayers::SceneAndUi) => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(Default::default()));
+            }
+            None => {}
+        }
+
+        // The scene goes through a RenderTarget so any panel or window can show it
+        let mut scene_size = None;
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                if !self.scene_in_window {
+                    scene_size = self.scene_image(ui);
+                }
+            });
+        if self.scene_in_window {
+            egui::Window::new("Scene")
+                .default_size([400.0, 300.0])
+                .resizable(true)
+                .show(ctx, |ui| scene_size = self.scene_image(ui));
+        }
+
+        // The image is only painted after this returns, so drawing the scene
+        // at the size just laid out still makes it into this frame
+        if let Some(size) = scene_size {
+            let pixels = size * ctx.pixels_per_point() * self.scene_scale;
+            let size = [pixels.x.round() as u32, pixels.y.round() as u32];
+            self.render_scene(size, scene_screenshot);
+        }
+
+        if !self.model_viewer.is_active() {
+            self.triangle_renderer.program.show_error_window(ctx);
+        }
+        self.model_viewer.show(ctx);
+        self.frame_stats.show(ctx);
+
+        // Show egui UI on top of the triangle
+        if self.show_color_picker {
+            egui::Window::new("Color Picker")
+                .default_size([300.0, 200.0])
+                .open(&mut self.show_color_picker)
+                .show(ctx, |ui| {
+                    ui.heading("Triangle Color");
+                    ui.separator();
+
+                    ui.label("Red:");
+                    ui.add(egui::Slider::new(&mut self.color[0], 0.0..=1.0));
+
+                    ui.label("Green:");
+                    ui.add(egui::Slider::new(&mut self.color[1], 0.0..=1.0));
+
+                    ui.label("Blue:");
+                    ui.add(egui::Slider::new(&mut self.color[2], 0.0..=1.0));
+
+                    ui.separator();
+                    ui.checkbox(&mut self.scene_in_window, "Show scene in a window");
+                    ui.add(
+                        egui::Slider::new(&mut self.scene_scale, 0.25..=2.0)
+                            .text("Resolution scale"),
+                    );
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("Save screenshot").clicked() {
+                            self.screenshot = Some(if self.screenshot_ui {
+                                ScreenshotLayers::SceneAndUi
+                            } else {
+                                ScreenshotLayers::Scene
+                            });
+                            ctx.request_repaint();
+                        }
+                        ui.checkbox(&mut self.screenshot_ui, "Include UI");
+                    });
+
+                    ui.separator();
+                    ui.label("Press SPACE to toggle this window, F3 for frame timing");
+                    ui.label(
+                        "Drag the scene to orbit, right-drag to pan, scroll to zoom, \
+                         P to switch projection",
+                    );
+                });
+        }
+    }
+
+    /// Show the scene texture filling the space left in `ui`, and return that
+    /// size in points. Dragging and scrolling the image moves the camera.
+    fn scene_image(&mut self, ui: &mut egui::Ui) -> Option<egui::Vec2> {
+        let texture = self.scene_texture?;
+        let size = ui.available_size().max(egui::Vec2::splat(1.0));
+        // GL textures start at the bottom row, egui images at the top
+        let response = ui.add(
+            egui::Image::new(egui::load::SizedTexture::new(texture, size))
+                .uv(egui::Rect::from_min_max(
+                    egui::pos2(0.0, 1.0),
+                    egui::pos2(1.0, 0.0),
+                ))
+                .sense(egui::Sense::drag()),
+        );
+
+        // The camera works in scene pixels, egui in points
+        let scale = ui.ctx().pixels_per_point() * self.scene_scale;
+        let delta = response.drag_delta() * scale;
+        let camera = self.camera_mut();
+        if response.dragged_by(egui::PointerButton::Primary) {
+            camera.orbit([delta.x, delta.y]);
+        } else if response.dragged_by(egui::PointerButton::Secondary)
+            || response.dragged_by(egui::PointerButton::Middle)
+        {
+            camera.pan([delta.x, delta.y]);
+        }
+        if response.hovered() {
+            let scroll = ui.input(|i| i.raw_scroll_delta.y);
+            if scroll != 0.0 {
+                // egui reports lines as 50 points, like winit's pixel deltas
+                camera.zoom(scroll / 50.0);
+            }
+        }
+        Some(size)
+    }
+
+    /// The camera of whatever is shown: the model if one is loaded, else the
+    /// triangle.
+    fn camera_mut(&mut self) -> &mut OrbitCamera {
+        if self.model_viewer.is_active() {
+            &mut self.model_viewer.camera
+        } else {
+            &mut self.camera
+        }
+    }
+
+    fn render_scene(&mut self, size: [u32; 2], screenshot: bool) {
+        let start = Instant::now();
+        if let Err(err) = self.scene.resize(size) {
+            log::error!(
+                "Failed to resize the scene to {}x{}: {err}",
+                size[0],
+                size[1]
+            );
+            return;
+        }
+        let scene_size = self.scene.size();
+        self.camera.set_viewport(scene_size);
+        self.model_viewer.camera.set_viewport(scene_size);
+        let triangle_renderer = &mut self.triangle_renderer;
+        let model_viewer = &mut self.model_viewer;
+        let camera = &self.camera;
+        let color = self.color;
+        let image = self.scene.draw(|gl| {
+            unsafe {
+                gl.clear_color(0.1, 0.2, 0.3, 1.0);
+                gl.clear(glow::COLOR_BUFFER_BIT);
+            }
+            if model_viewer.is_active() {
+                model_viewer.draw();
+            } else {
+                triangle_renderer.paint(gl, color, camera);
+            }
+            screenshot.then(|| capture::read_framebuffer(gl, scene_size))
+        });
+        self.draw_time = start.elapsed();
+
+        if let Some(image) = image {
+            save_screenshot(image);
+        }
+    }
+}
+
+fn save_color_image(image: &egui::ColorImage) {
+    let [width, height] = image.size;
+    save_screenshot(
+        image::RgbaImage::from_raw(width as u32, height as u32, image.as_raw().to_vec())
+            .expect("buffer matches image size"),
+    );
+}
+
+fn save_screenshot(image: image::RgbaImage) {
+    match capture::save_screenshot(image) {
+        Ok(path) => log::info!("Saved screenshot to {}", path.display()),
+        Err(err) => log::error!("Failed to save screenshot: {err}"),
+    }
+}
+
+impl eframe::App for MyApp {
+    // Called by eframe on shutdown and every `auto_save_interval`
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let state = UiState {
+            color: self.color,
+            show_color_picker: self.show_color_picker,
+        };
+        eframe::set_value(storage, eframe::APP_KEY, &state);
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // The painter only exists once eframe is running, so register here
+        if self.scene_texture.is_none() {
+            self.scene_texture = Some(frame.register_native_glow_texture(self.scene.texture()));
+        }
+
+        // eframe tessellates, paints and swaps after `update` returns, so the
+        // previous frame's total CPU time is only known now. The phases in
+        // between are not observable from here.
+        if let Some(cpu_usage) = frame.info().cpu_usage {
+            self.frame_stats
+                .set_cpu_time(Duration::from_secs_f32(cpu_usage));
+        }
+        self.frame_stats.begin_frame();
+
+        // The watcher already requested this repaint when a file changed
+        if self
+            .shader_watcher
+            .as_ref()
+            .is_some_and(ShaderWatcher::take_changed)
+        {
+            self.triangle_renderer.program.poll();
+            self.model_viewer.poll_shaders();
+        }
+
+        // Drawing the scene happens inside `ui`; keep it out of the UI time
+        self.draw_time = Duration::ZERO;
+        let start = Instant::now();
+        self.ui(ctx);
+        self.frame_stats.record(Phase::Draw, self.draw_time);
+        self.frame_stats
+            .record(Phase::Ui, start.elapsed().saturating_sub(self.draw_time));
+
+        // eframe already repaints on input, while egui animates and when the
+        // shader watcher sees an edit; only benchmarking asks for more
+        if self.continuous {
+            ctx.request_repaint();
+        }
+    }
+}
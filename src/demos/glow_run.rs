@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Instant;
+
+use glow::*;
+use winit::application::ApplicationHandler;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::WindowId;
+use winit_test::capture;
+use winit_test::cli::{CommonArgs, GlArgs};
+use winit_test::gl_config::ConfigRequirements;
+use winit_test::gl_window::{GlWindow, GlWindowBuilder};
+use winit_test::headless::HeadlessOptions;
+use winit_test::math::Mat4;
+use winit_test::mesh::{self, Mesh};
+use winit_test::shader::ShaderStage;
+use winit_test::shader_reload::{POLL_INTERVAL, ReloadableProgram, shader_path};
+use winit_test::texture::{self, ColorSpace, FilterPreset, Sampling, Texture2D, Wrap};
+
+/// The texture shown on the quad.
+const TEXTURE: &str = "uv_grid.jpg";
+
+/// A gradient triangle, or a textured quad, drawn with glow.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    #[command(flatten)]
+    gl: GlArgs,
+
+    /// Start with the textured quad instead of the triangle
+    #[arg(long)]
+    textured_quad: bool,
+}
+
+impl Args {
+    fn demo(&self) -> Demo {
+        if self.textured_quad {
+            Demo::TexturedQuad
+        } else {
+            Demo::Triangle
+        }
+    }
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    if let Some(options) = args.common.headless() {
+        return run_headless(&options, &args);
+    }
+
+    let event_loop = EventLoop::new()?;
+    let mut app = Application::new(args);
+    event_loop.run_app(&mut app).map_err(Into::into)
+}
+
+#[cfg(not(target_vendor = "apple"))]
+fn run_headless(options: &HeadlessOptions, args: &Args) -> Result<(), Box<dyn Error>> {
+    use winit_test::headless::HeadlessContext;
+
+    let size = options.size;
+    let context = HeadlessContext::with_apis(size[0], size[1], &args.gl.apis())?;
+    let mut renderer = Renderer::new(context.gl.clone(), &context.api.glsl_version_header())?;
+    renderer.demo = args.demo();
+    if let Some(color) = args.common.color {
+        renderer.background = color;
+    }
+
+    for _ in 0..options.frames {
+        renderer.draw(size);
+    }
+
+    context.read_pixels().save(&options.output)?;
+    log::info!(
+        "Wrote frame {} to {}",
+        options.frames,
+        options.output.display()
+    );
+    Ok(())
+}
+
+#[cfg(target_vendor = "apple")]
+fn run_headless(_options: &HeadlessOptions, _args: &Args) -> Result<(), Box<dyn Error>> {
+    Err("headless rendering needs EGL, which is not available on this platform".into())
+}
+
+struct Application {
+    windows: HashMap<WindowId, WindowState>,
+    args: Args,
+    next_shader_poll: Instant,
+}
+
+// Fields drop in order: the renderer's GL objects go before the context.
+struct WindowState {
+    renderer: Renderer,
+    gl_window: GlWindow,
+    /// Save the next frame drawn (F12).
+    screenshot_requested: bool,
+}
+
+/// What is drawn; T switches between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Demo {
+    Triangle,
+    TexturedQuad,
+}
+
+/// The gradient triangle and the textured quad, independent of whether they
+/// are drawn to a window or offscreen.
+struct Renderer {
+    gl: Arc<glow::Context>,
+    demo: Demo,
+    /// The clear color, from `--color`.
+    background: [f32; 3],
+    program: ReloadableProgram,
+    triangle: Mesh,
+
+    quad_program: ReloadableProgram,
+    quad: Mesh,
+    texture: Texture2D,
+    filter: FilterPreset,
+}
+
+impl Renderer {
+    fn new(gl: Arc<glow::Context>, glsl_version_header: &str) -> Result<Self, Box<dyn Error>> {
+        let triangle = Mesh::new(gl.clone(), &mesh::TRIANGLE, &[0, 1, 2])?;
+
+        let program = load_program(&gl, glsl_version_header, "gradient")?;
+
+        let quad = Mesh::new(gl.clone(), &mesh::QUAD, &mesh::QUAD_INDICES)?;
+        let quad_program = load_program(&gl, glsl_version_header, "textured")?;
+
+        let filter = FilterPreset::Trilinear;
+        let texture = Texture2D::load(
+            gl.clone(),
+            &texture::texture_path(TEXTURE),
+            ColorSpace::Srgb,
+            Sampling::preset(filter, Wrap::Repeat),
+        )?;
+
+        Ok(Self {
+            gl,
+            demo: Demo::Triangle,
+            background: [0.1, 0.2, 0.3],
+            program,
+            triangle,
+            quad_program,
+            quad,
+            texture,
+            filter,
+        })
+    }
+
+    fn draw(&self, [width, height]: [u32; 2]) {
+        let gl = &self.gl;
+        let [r, g, b] = self.background;
+        unsafe {
+            gl.viewport(0, 0, width as i32, height as i32);
+            gl.clear_color(r, g, b, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+        }
+        let (program, mesh) = match self.demo {
+            Demo::Triangle => (&self.program, &self.triangle),
+            Demo::TexturedQuad => (&self.quad_program, &self.quad),
+        };
+        let Some(program) = program.program() else {
+            return;
+        };
+        program.use_program();
+        // The shaders are shared with the scene gallery; this demo draws in clip space
+        unsafe {
+            gl.uniform_matrix_4_f32_slice(
+                program.uniform_location("u_view_projection").as_ref(),
+                false,
+                Mat4::IDENTITY.as_slice(),
+            );
+        }
+        if self.demo == Demo::TexturedQuad {
+            self.texture.bind(program, "u_texture", 0);
+        }
+        mesh.draw();
+    }
+
+    /// Pick up shader edits, returning `true` when the frame needs redrawing.
+    fn poll_shaders(&mut self) -> bool {
+        let triangle = self.program.poll();
+        let quad = self.quad_program.poll();
+        match self.demo {
+            Demo::Triangle => triangle,
+            Demo::TexturedQuad => quad,
+        }
+    }
+
+    /// Act on a key press, returning `false` for keys it doesn't use.
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        let sampling = self.texture.sampling();
+        match key {
+            KeyCode::KeyT => {
+                self.demo = match self.demo {
+                    Demo::Triangle => Demo::TexturedQuad,
+                    Demo::TexturedQuad => Demo::Triangle,
+                };
+            }
+            KeyCode::KeyF => {
+                let index = FilterPreset::ALL
+                    .iter()
+                    .position(|&filter| filter == self.filter);
+                self.filter = FilterPreset::ALL
+                    [index.map_or(0, |index| (index + 1) % FilterPreset::ALL.len())];
+                self.texture
+                    .set_sampling(Sampling::preset(self.filter, sampling.wrap));
+            }
+            KeyCode::KeyW => {
+                let index = Wrap::ALL.iter().position(|&wrap| wrap == sampling.wrap);
+                let wrap = Wrap::ALL[index.map_or(0, |index| (index + 1) % Wrap::ALL.len())];
+                self.texture.set_sampling(Sampling { wrap, ..sampling });
+            }
+            // The color space is part of the texture's format, so reload it
+            KeyCode::KeyS => {
+                let color_space = match self.texture.color_space() {
+                    ColorSpace::Srgb => ColorSpace::Linear,
+                    ColorSpace::Linear => ColorSpace::Srgb,
+                };
+                match Texture2D::load(
+                    self.gl.clone(),
+                    &texture::texture_path(TEXTURE),
+                    color_space,
+                    sampling,
+                ) {
+                    Ok(texture) => self.texture = texture,
+                    Err(err) => log::error!("Failed to reload {TEXTURE}: {err}"),
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// The window title, listing the texture settings while the quad is shown.
+    fn title(&self) -> String {
+        match self.demo {
+            Demo::Triangle => "Glow OpenGL Window - Press T for a textured quad".to_owned(),
+            Demo::TexturedQuad => {
+                let sampling = self.texture.sampling();
+                format!(
+                    "Glow OpenGL Window - {TEXTURE}: {} (F), {:?} (W), {:?} (S)",
+                    self.filter.name(),
+                    sampling.wrap,
+                    self.texture.color_space()
+                )
+            }
+        }
+    }
+}
+
+/// Load `shaders/<name>.vert` and `shaders/<name>.frag`.
+fn load_program(
+    gl: &Arc<glow::Context>,
+    glsl_version_header: &str,
+    name: &str,
+) -> Result<ReloadableProgram, String> {
+    ReloadableProgram::load_required(
+        gl.clone(),
+        glsl_version_header,
+        &[
+            (ShaderStage::Vertex, &shader_path(&format!("{name}.vert"))),
+            (ShaderStage::Fragment, &shader_path(&format!("{name}.frag"))),
+        ],
+    )
+}
+
+impl Application {
+    fn new(args: Args) -> Self {
+        Self {
+            windows: HashMap::new(),
+            args,
+            next_shader_poll: Instant::now(),
+        }
+    }
+
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Box<dyn Error>> {
+        let args = &self.args;
+        let window_attributes = args
+            .common
+            .window_attributes()
+            .with_title("Glow OpenGL Window");
+
+        let gl_window = GlWindowBuilder::new(window_attributes)
+            .with_config_requirements(args.gl.config_requirements(ConfigRequirements::default()))
+            .with_apis(args.gl.apis())
+            .build(event_loop)?;
+        gl_window.set_vsync(args.gl.vsync);
+        let mut renderer =
+            Renderer::new(gl_window.gl.clone(), &gl_window.api.glsl_version_header())?;
+        renderer.demo = args.demo();
+        if let Some(color) = args.common.color {
+            renderer.background = color;
+        }
+        gl_window.window.set_title(&renderer.title());
+
+        self.windows.insert(
+            gl_window.window.id(),
+            WindowState {
+                renderer,
+                gl_window,
+                screenshot_requested: false,
+            },
+        );
+
+        Ok(())
+    }
+}
+
+impl ApplicationHandler for Application {
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let window_state = match self.windows.get_mut(&window_id) {
+            Some(window) => window,
+            None => return,
+        };
+
+        match event {
+            WindowEvent::KeyboardInput { event, .. }
+                if event.state.is_pressed()
+                    && event.physical_key == PhysicalKey::Code(KeyCode::F12) =>
+            {
+                window_state.screenshot_requested = true;
+                window_state.gl_window.window.request_redraw();
+            }
+            WindowEvent::KeyboardInput { event, .. }
+                if event.state.is_pressed()
+                    && let PhysicalKey::Code(key) = event.physical_key
+                    && window_state.renderer.handle_key(key) =>
+            {
+                let window = &window_state.gl_window.window;
+                window.set_title(&window_state.renderer.title());
+                window.request_redraw();
+            }
+            WindowEvent::CloseRequested => {
+                self.windows.remove(&window_id);
+                if self.windows.is_empty() {
+                    event_loop.exit();
+                }
+            }
+            WindowEvent::RedrawRequested => {
+                //let window_state = self.windows.get_mut(&window_id).unwrap();
+
+                // Begin egui frame
+                // let raw_input = window_state
+                //     .egui_state
+                //     .take_egui_input(&window_state.window);
+                // let full_output = window_state.egui_ctx.run(raw_input, |ctx| {
+                //     egui::CentralPanel::default().show(ctx, |ui| {
+                //         ui.heading("My egui Application");
+                //         ui.horizontal(|ui| {
+                //             let name_label = ui.label("Your name: ");
+                //             ui.text_edit_singleline(&mut self.name)
+                //                 .labelled_by(name_label.id);
+                //         });
+                //         ui.add(egui::Slider::new(&mut self.age, 0..=120).text("age"));
+                //         if ui.button("Increment").clicked() {
+                //             self.age += 1;
+                //         }
+                //         ui.label(format!("Hello '{}', age {}", self.name, self.age));
+                //     });
+                // });
+
+                // Render egui on top
+                // let clipped_primitives = window_state
+                //     .egui_ctx
+                //     .tessellate(full_output.shapes, full_output.pixels_per_point);
+
+                // let size = window_state.window.inner_size();
+                // window_state.egui_painter.paint_primitives(
+                //     [size.width, size.height],
+                //     full_output.pixels_per_point,
+                //     &clipped_primitives,
+                // );
+
+                // window_state
+                //     .egui_state
+                //     .handle_platform_output(&window_state.window, full_output.platform_output);
+
+                let gl_window = &window_state.gl_window;
+                let size = gl_window.window.inner_size();
+                window_state.renderer.draw([size.width, size.height]);
+
+                // Read back before swapping, after which the back buffer is undefined
+                if std::mem::take(&mut window_state.screenshot_requested) {
+                    let image = capture::read_framebuffer(
+                        &window_state.renderer.gl,
+                        [size.width, size.height],
+                    );
+                    match capture::save_screenshot(image) {
+                        Ok(path) => log::info!("Saved screenshot to {}", path.display()),
+                        Err(err) => log::error!("Failed to save screenshot: {err}"),
+                    }
+                }
+                if let Err(err) = gl_window.swap_buffers() {
+                    log::error!("Failed to swap buffers: {err}");
+                }
+            }
+            WindowEvent::Resized(size) if window_state.gl_window.resize(size) => {
+                window_state.gl_window.window.request_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.windows.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        if now >= self.next_shader_poll {
+            self.next_shader_poll = now + POLL_INTERVAL;
+            for window_state in self.windows.values_mut() {
+                if window_state.renderer.poll_shaders() {
+                    window_state.gl_window.window.request_redraw();
+                }
+            }
+        }
+        event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_shader_poll));
+    }
+
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.windows.is_empty()
+            && let Err(err) = self.create_window(event_loop)
+        {
+            log::error!("Failed to create window: {err}");
+            event_loop.exit();
+        }
+    }
+}
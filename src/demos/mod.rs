@@ -0,0 +1,7 @@
+//! The demos the launcher runs, one module per subcommand. Each has an `Args`
+//! with the demo's options and a `run` that takes them.
+
+pub mod ai_one;
+pub mod eframe_winit;
+pub mod glow_run;
+pub mod softbuffer;
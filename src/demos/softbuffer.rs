@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use winit::application::ApplicationHandler;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::window::{WindowAttributes, WindowId};
+use winit_test::cli::CommonArgs;
+use winit_test::headless::{HeadlessOptions, egui_input};
+use winit_test::mesh;
+use winit_test::raster::{self, Target, Vertex};
+use winit_test::redraw::{self, RedrawScheduler};
+use winit_test::soft_painter::SoftPainter;
+use winit_test::soft_window::SoftWindow;
+
+/// The gradient triangle and an egui color picker, drawn on the CPU into a
+/// softbuffer window.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// Open the color picker at startup
+    #[arg(long)]
+    show_color_picker: bool,
+
+    /// Redraw continuously instead of when something changed
+    #[arg(long)]
+    continuous: bool,
+}
+
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    if let Some(options) = args.common.headless() {
+        return run_headless(&options, &args);
+    }
+
+    let event_loop = EventLoop::new()?;
+    let mut app = Application::new(&args);
+    event_loop.run_app(&mut app).map_err(Into::into)
+}
+
+/// Render into a plain buffer instead of a window. Unlike the GL demos this
+/// works everywhere, since nothing here touches the GPU.
+fn run_headless(options: &HeadlessOptions, args: &Args) -> Result<(), Box<dyn Error>> {
+    let size = options.size;
+    let mut pixels = vec![0; size[0] as usize * size[1] as usize];
+    let mut renderer = Renderer::new(args.show_color_picker, args.common.color);
+
+    for frame in 0..options.frames {
+        let mut target = Target::new(&mut pixels, size[0], size[1]);
+        renderer.render(&mut target, egui_input(frame, size));
+    }
+
+    raster::to_rgba_image(&pixels, size[0], size[1]).save(&options.output)?;
+    log::info!(
+        "Wrote frame {} to {}",
+        options.frames,
+        options.output.display()
+    );
+    Ok(())
+}
+
+struct Application {
+    windows: HashMap<WindowId, WindowState>,
+    /// Size and fullscreen from the command line.
+    window_attributes: WindowAttributes,
+    show_color_picker: bool,
+    /// Background color from `--color`.
+    background: Option<[f32; 3]>,
+    continuous: bool,
+}
+
+struct WindowState {
+    renderer: Renderer,
+    egui_winit: egui_winit::State,
+    soft_window: SoftWindow,
+    redraw: RedrawScheduler,
+}
+
+/// The gradient triangle `glow_run` draws plus an egui UI, all on the CPU.
+struct Renderer {
+    egui_ctx: egui::Context,
+    egui_painter: SoftPainter,
+
+    show_color_picker: bool,
+    background: [f32; 3],
+}
+
+impl Renderer {
+    fn new(show_color_picker: bool, background: Option<[f32; 3]>) -> Self {
+        Self {
+            egui_ctx: egui::Context::default(),
+            egui_painter: SoftPainter::new(),
+            show_color_picker,
+            background: background.unwrap_or([0.1, 0.2, 0.3]),
+        }
+    }
+
+    /// Draw one frame into `target` and return egui's platform output
+    /// (cursor changes, clipboard, etc.) for the caller to handle, together
+    /// with how soon the next frame is wanted.
+    fn render(
+        &mut self,
+        target: &mut Target,
+        raw_input: egui::RawInput,
+    ) -> (egui::PlatformOutput, Duration) {
+        // The triangle `glow_run` draws, gradient included
+        let (width, height) = (target.width, target.height);
+        let vertices = mesh::TRIANGLE.map(|vertex| Vertex {
+            position: raster::ndc_to_pixel([vertex.position[0], vertex.position[1]], width, height),
+            color: vertex.color,
+        });
+
+        target.clear(self.background);
+        target.fill_triangle(&vertices, None);
+
+        let show_color_picker = &mut self.show_color_picker;
+        let background = &mut self.background;
+        let full_output = self.egui_ctx.run(raw_input, |ctx| {
+            if *show_color_picker {
+                egui::Window::new("Color Picker")
+                    .default_size([300.0, 200.0])
+                    .open(show_color_picker)
+                    .show(ctx, |ui| {
+                        ui.heading("Background Color");
+                        ui.separator();
+
+                        ui.label("Red:");
+                        ui.add(egui::Slider::new(&mut background[0], 0.0..=1.0));
+
+                        ui.label("Green:");
+                        ui.add(egui::Slider::new(&mut background[1], 0.0..=1.0));
+
+                        ui.label("Blue:");
+                        ui.add(egui::Slider::new(&mut background[2], 0.0..=1.0));
+
+                        ui.separator();
+                        ui.label("Press SPACE to toggle this window");
+                    });
+            }
+        });
+
+        let repaint_delay = redraw::repaint_delay(&full_output);
+        let clipped_primitives = self
+            .egui_ctx
+            .tessellate(full_output.shapes, full_output.pixels_per_point);
+        self.egui_painter.paint_and_update_textures(
+            target,
+            full_output.pixels_per_point,
+            &clipped_primitives,
+            &full_output.textures_delta,
+        );
+
+        (full_output.platform_output, repaint_delay)
+    }
+}
+
+impl Application {
+    fn new(args: &Args) -> Self {
+        Self {
+            windows: HashMap::new(),
+            window_attributes: args.common.window_attributes(),
+            show_color_picker: args.show_color_picker,
+            background: args.common.color,
+            continuous: args.continuous,
+        }
+    }
+
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Box<dyn Error>> {
+        let soft_window = SoftWindow::new(
+            event_loop,
+            self.window_attributes
+                .clone()
+                .with_title("Softbuffer Window - Press SPACE for color picker"),
+        )?;
+        let window = &soft_window.window;
+
+        let renderer = Renderer::new(self.show_color_picker, self.background);
+        let egui_winit = egui_winit::State::new(
+            renderer.egui_ctx.clone(),
+            egui::ViewportId::ROOT,
+            window,
+            Some(window.scale_factor() as f32),
+            None,
+            None,
+        );
+        window.focus_window();
+
+        self.windows.insert(
+            window.id(),
+            WindowState {
+                renderer,
+                egui_winit,
+                soft_window,
+                redraw: RedrawScheduler::new(self.continuous),
+            },
+        );
+        Ok(())
+    }
+}
+
+impl ApplicationHandler for Application {
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let window_state = match self.windows.get_mut(&window_id) {
+            Some(window) => window,
+            None => return,
+        };
+
+        // Application shortcuts are checked before egui sees the event
+        if let WindowEvent::KeyboardInput { event, .. } = &event
+            && event.state == winit::event::ElementState::Pressed
+            && event.physical_key
+                == winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::Space)
+        {
+            window_state.renderer.show_color_picker = !window_state.renderer.show_color_picker;
+            window_state.soft_window.window.request_redraw();
+        }
+
+        let event_response = window_state
+            .egui_winit
+            .on_window_event(&window_state.soft_window.window, &event);
+        if event_response.repaint {
+            window_state.soft_window.window.request_redraw();
+        }
+
+        match event {
+            WindowEvent::CloseRequested => {
+                self.windows.remove(&window_id);
+                if self.windows.is_empty() {
+                    event_loop.exit();
+                }
+            }
+            WindowEvent::RedrawRequested => {
+                let window = &window_state.soft_window.window;
+                let raw_input = window_state.egui_winit.take_egui_input(window);
+
+                let renderer = &mut window_state.renderer;
+                match window_state
+                    .soft_window
+                    .present(|target| renderer.render(target, raw_input))
+                {
+                    Ok(Some((platform_output, repaint_delay))) => {
+                        window_state.egui_winit.handle_platform_output(
+                            &window_state.soft_window.window,
+                            platform_output,
+                        );
+                        window_state.redraw.frame_drawn(repaint_delay);
+                    }
+                    Ok(None) => {}
+                    Err(err) => log::error!("Failed to present frame: {err}"),
+                }
+            }
+            WindowEvent::Resized(_) => {
+                window_state.soft_window.window.request_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.windows.is_empty()
+            && let Err(err) = self.create_window(event_loop)
+        {
+            log::error!("Failed to create window: {err}");
+            event_loop.exit();
+        }
+    }
+
+    // Redraw when egui asks for it, sleeping in between
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        let deadlines = self.windows.values_mut().filter_map(|window_state| {
+            window_state
+                .redraw
+                .poll(&window_state.soft_window.window, now)
+        });
+        redraw::set_control_flow(event_loop, deadlines);
+    }
+}
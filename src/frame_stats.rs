@@ -0,0 +1,250 @@
+//! Frame timing collected per phase and shown in an egui overlay window.
+//!
+//! A frame's phases are timed with [`Instant`]s by the code that runs them and
+//! handed to [`FrameStats::record`]. The sample is closed by the next
+//! [`FrameStats::begin_frame`], so work that happens after the UI is built
+//! (painting, swapping buffers) still lands in the right frame; the overlay
+//! therefore always shows the frames before the current one.
+//!
+//! GPU pass times from [`crate::gpu_timer`] come in a few frames late and are
+//! stored with the frame in which they became available.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use egui_plot::{Legend, Line, Plot};
+
+use crate::gpu_timer::{GpuPass, GpuTimes};
+
+/// How much history the overlay keeps and plots.
+const HISTORY: Duration = Duration::from_secs(5);
+
+/// A timed part of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Issuing the app's own draw calls (CPU side).
+    Draw,
+    /// Running the egui UI code.
+    Ui,
+    /// `Context::tessellate`.
+    Tessellate,
+    /// Uploading egui textures and painting its primitives.
+    Paint,
+    /// Presenting: `swap_buffers` or the softbuffer equivalent.
+    Swap,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [
+        Self::Draw,
+        Self::Ui,
+        Self::Tessellate,
+        Self::Paint,
+        Self::Swap,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Draw => "Draw",
+            Self::Ui => "egui UI",
+            Self::Tessellate => "Tessellate",
+            Self::Paint => "Paint",
+            Self::Swap => "Swap",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    start: Instant,
+    /// Time from this frame's start to the next one's.
+    frame_time: Duration,
+    /// Reported CPU time, or the sum of `phases` when none was reported.
+    cpu_time: Option<Duration>,
+    phases: [Option<Duration>; Phase::ALL.len()],
+    gpu: GpuTimes,
+}
+
+impl Sample {
+    fn new(start: Instant) -> Self {
+        Self {
+            start,
+            frame_time: Duration::ZERO,
+            cpu_time: None,
+            phases: [None; Phase::ALL.len()],
+            gpu: [None; GpuPass::ALL.len()],
+        }
+    }
+
+    fn cpu_time(&self) -> Duration {
+        self.cpu_time
+            .unwrap_or_else(|| self.phases.iter().flatten().sum())
+    }
+}
+
+/// Rolling frame timing history for one window.
+#[derive(Debug, Default)]
+pub struct FrameStats {
+    /// Whether [`FrameStats::show`] draws the overlay.
+    pub visible: bool,
+    history: VecDeque<Sample>,
+    current: Option<Sample>,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Close the previous frame's sample and start timing a new frame.
+    pub fn begin_frame(&mut self) {
+        let now = Instant::now();
+        if let Some(mut sample) = self.current.take() {
+            sample.frame_time = now - sample.start;
+            self.history.push_back(sample);
+        }
+        while self
+            .history
+            .front()
+            .is_some_and(|sample| now - sample.start > HISTORY)
+        {
+            self.history.pop_front();
+        }
+        self.current = Some(Sample::new(now));
+    }
+
+    /// Add `duration` to `phase` of the current frame.
+    pub fn record(&mut self, phase: Phase, duration: Duration) {
+        if let Some(sample) = &mut self.current {
+            let total = sample.phases[phase.index()].get_or_insert(Duration::ZERO);
+            *total += duration;
+        }
+    }
+
+    /// Report the current frame's total CPU time, for when not all of it is
+    /// covered by recorded phases (e.g. eframe's own painting).
+    pub fn set_cpu_time(&mut self, cpu_time: Duration) {
+        if let Some(sample) = &mut self.current {
+            sample.cpu_time = Some(cpu_time);
+        }
+    }
+
+    /// Store GPU pass times that finished during the current frame.
+    pub fn record_gpu(&mut self, times: GpuTimes) {
+        if let Some(sample) = &mut self.current {
+            for (slot, time) in sample.gpu.iter_mut().zip(times) {
+                if time.is_some() {
+                    *slot = time;
+                }
+            }
+        }
+    }
+
+    /// The newest GPU time of `pass` in the kept history.
+    fn latest_gpu(&self, pass: GpuPass) -> Option<Duration> {
+        self.history
+            .iter()
+            .rev()
+            .find_map(|sample| sample.gpu[pass.index()])
+    }
+
+    /// Frames per second over the kept history.
+    pub fn fps(&self) -> f32 {
+        let total: Duration = self.history.iter().map(|sample| sample.frame_time).sum();
+        if total.is_zero() {
+            0.0
+        } else {
+            self.history.len() as f32 / total.as_secs_f32()
+        }
+    }
+
+    /// Show the "Frame Timing" window if [`FrameStats::visible`] is set.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if !self.visible {
+            return;
+        }
+        // Keep frames coming while the overlay is open so the graph scrolls
+        ctx.request_repaint();
+
+        let fps = self.fps();
+        let gpu = GpuPass::ALL.map(|pass| self.latest_gpu(pass));
+        let history = &self.history;
+        egui::Window::new("Frame Timing")
+            .open(&mut self.visible)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                let Some(last) = history.back() else {
+                    ui.label("Waiting for frames…");
+                    return;
+                };
+                let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+                ui.label(format!("FPS: {fps:.1}"));
+                ui.label(format!("Frame time: {:.2} ms", ms(last.frame_time)));
+                ui.label(format!("CPU time: {:.2} ms", ms(last.cpu_time())));
+
+                egui::Grid::new("frame_phases")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for phase in Phase::ALL {
+                            if let Some(duration) = last.phases[phase.index()] {
+                                ui.label(phase.label());
+                                ui.label(format!("{:.2} ms", ms(duration)));
+                                ui.end_row();
+                            }
+                        }
+                        for (pass, time) in GpuPass::ALL.into_iter().zip(gpu) {
+                            if let Some(duration) = time {
+                                ui.label(pass.label());
+                                ui.label(format!("{:.2} ms", ms(duration)));
+                                ui.end_row();
+                            }
+                        }
+                    });
+
+                // Seconds relative to the newest sample, so the graph scrolls left
+                let newest = last.start;
+                let series = |value: &dyn Fn(&Sample) -> Option<Duration>| {
+                    history
+                        .iter()
+                        .filter_map(|sample| {
+                            let x = -(newest - sample.start).as_secs_f64();
+                            value(sample).map(|duration| [x, ms(duration)])
+                        })
+                        .collect::<Vec<_>>()
+                };
+
+                Plot::new("frame_times")
+                    .height(140.0)
+                    .legend(Legend::default())
+                    .include_x(-HISTORY.as_secs_f64())
+                    .include_x(0.0)
+                    .include_y(0.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .x_axis_label("s")
+                    .y_axis_label("ms")
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(Line::new("Frame", series(&|s| Some(s.frame_time))));
+                        plot_ui.line(Line::new("CPU", series(&|s| Some(s.cpu_time()))));
+                        for phase in Phase::ALL {
+                            let points = series(&|s| s.phases[phase.index()]);
+                            if !points.is_empty() {
+                                plot_ui.line(Line::new(phase.label(), points));
+                            }
+                        }
+                        for pass in GpuPass::ALL {
+                            let points = series(&|s| s.gpu[pass.index()]);
+                            if !points.is_empty() {
+                                plot_ui.line(Line::new(pass.label(), points));
+                            }
+                        }
+                    });
+            });
+    }
+}
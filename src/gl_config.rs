@@ -0,0 +1,265 @@
+//! Framebuffer config selection for [`GlWindowBuilder`](crate::gl_window::GlWindowBuilder).
+//!
+//! glutin hands the config picker every config the display offers. Instead of
+//! always taking the one with the most samples, [`ConfigRequirements`] rejects
+//! configs that miss a hard requirement and scores the rest, so the pick is
+//! the closest match rather than the biggest.
+
+use std::fmt;
+
+use glutin::config::{Config, GlConfig};
+
+/// Whether the config must, should or may be hardware accelerated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HardwareAcceleration {
+    Required,
+    #[default]
+    Preferred,
+    Any,
+}
+
+/// What the demos need from the default framebuffer.
+///
+/// Bit counts are minimums and `srgb`/`transparency` are hard requirements
+/// when set. `samples` is a preference: the exact count wins, more samples
+/// are preferred over fewer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigRequirements {
+    pub samples: u8,
+    pub srgb: bool,
+    pub alpha_bits: u8,
+    pub depth_bits: u8,
+    pub stencil_bits: u8,
+    pub transparency: bool,
+    pub hardware_acceleration: HardwareAcceleration,
+}
+
+impl Default for ConfigRequirements {
+    fn default() -> Self {
+        Self {
+            samples: 4,
+            srgb: false,
+            alpha_bits: 8,
+            depth_bits: 0,
+            stencil_bits: 0,
+            transparency: false,
+            hardware_acceleration: HardwareAcceleration::Preferred,
+        }
+    }
+}
+
+impl ConfigRequirements {
+    /// Score `config`, higher is better. `None` means it misses a hard requirement.
+    pub fn score(&self, config: &ConfigSummary) -> Option<i32> {
+        let meets_requirements = config.alpha_bits >= self.alpha_bits
+            && config.depth_bits >= self.depth_bits
+            && config.stencil_bits >= self.stencil_bits
+            && (!self.srgb || config.srgb)
+            && (!self.transparency || config.transparency == Some(true))
+            && (self.hardware_acceleration != HardwareAcceleration::Required
+                || config.hardware_accelerated);
+        if !meets_requirements {
+            return None;
+        }
+
+        let samples = i32::from(config.samples);
+        let wanted = i32::from(self.samples);
+        let mut score = 10_000;
+
+        // Missing samples hurts more than having a few to spare
+        score -= if samples >= wanted {
+            (samples - wanted) * 10
+        } else {
+            (wanted - samples) * 40
+        };
+
+        if self.hardware_acceleration == HardwareAcceleration::Preferred
+            && config.hardware_accelerated
+        {
+            score += 1_000;
+        }
+
+        // Among otherwise equal configs take the one wasting the least memory
+        score -= i32::from(config.alpha_bits - self.alpha_bits);
+        score -= i32::from(config.depth_bits - self.depth_bits);
+        score -= i32::from(config.stencil_bits - self.stencil_bits);
+
+        Some(score)
+    }
+
+    /// Pick the best scoring config, or `None` when none qualifies.
+    pub fn pick<'a>(&self, configs: impl IntoIterator<Item = &'a Config>) -> Option<&'a Config> {
+        self.best(
+            configs
+                .into_iter()
+                .map(|config| (ConfigSummary::from(config), config)),
+        )
+    }
+
+    /// The item with the best scoring summary, split from [`Self::pick`] so
+    /// it works without a display to get configs from.
+    fn best<T>(&self, candidates: impl IntoIterator<Item = (ConfigSummary, T)>) -> Option<T> {
+        candidates
+            .into_iter()
+            .filter_map(|(summary, item)| Some((self.score(&summary)?, item)))
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, item)| item)
+    }
+}
+
+/// The properties of a [`Config`] that [`ConfigRequirements`] looks at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSummary {
+    pub samples: u8,
+    pub srgb: bool,
+    pub alpha_bits: u8,
+    pub depth_bits: u8,
+    pub stencil_bits: u8,
+    /// `None` when the platform can't tell.
+    pub transparency: Option<bool>,
+    pub hardware_accelerated: bool,
+}
+
+impl From<&Config> for ConfigSummary {
+    fn from(config: &Config) -> Self {
+        Self {
+            samples: config.num_samples(),
+            srgb: config.srgb_capable(),
+            alpha_bits: config.alpha_size(),
+            depth_bits: config.depth_size(),
+            stencil_bits: config.stencil_size(),
+            transparency: config.supports_transparency(),
+            hardware_accelerated: config.hardware_accelerated(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x MSAA, alpha {}, depth {}, stencil {}",
+            self.samples, self.alpha_bits, self.depth_bits, self.stencil_bits
+        )?;
+        if self.srgb {
+            f.write_str(", sRGB")?;
+        }
+        if self.transparency == Some(true) {
+            f.write_str(", transparent")?;
+        }
+        if !self.hardware_accelerated {
+            f.write_str(", software")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(samples: u8) -> ConfigSummary {
+        ConfigSummary {
+            samples,
+            srgb: false,
+            alpha_bits: 8,
+            depth_bits: 0,
+            stencil_bits: 0,
+            transparency: None,
+            hardware_accelerated: true,
+        }
+    }
+
+    #[test]
+    fn rejects_configs_missing_a_hard_requirement() {
+        let requirements = ConfigRequirements {
+            srgb: true,
+            depth_bits: 24,
+            ..ConfigRequirements::default()
+        };
+        let config = ConfigSummary {
+            depth_bits: 24,
+            ..summary(4)
+        };
+        assert_eq!(requirements.score(&config), None);
+        let config = ConfigSummary {
+            srgb: true,
+            ..summary(4)
+        };
+        assert_eq!(requirements.score(&config), None);
+        let config = ConfigSummary {
+            srgb: true,
+            depth_bits: 24,
+            ..summary(4)
+        };
+        assert!(requirements.score(&config).is_some());
+    }
+
+    #[test]
+    fn transparency_must_be_known_when_required() {
+        let requirements = ConfigRequirements {
+            transparency: true,
+            ..ConfigRequirements::default()
+        };
+        assert_eq!(requirements.score(&summary(4)), None);
+        let config = ConfigSummary {
+            transparency: Some(true),
+            ..summary(4)
+        };
+        assert!(requirements.score(&config).is_some());
+    }
+
+    #[test]
+    fn required_acceleration_rejects_software_configs() {
+        let software = ConfigSummary {
+            hardware_accelerated: false,
+            ..summary(4)
+        };
+        let required = ConfigRequirements {
+            hardware_acceleration: HardwareAcceleration::Required,
+            ..ConfigRequirements::default()
+        };
+        assert_eq!(required.score(&software), None);
+
+        let preferred = ConfigRequirements::default();
+        assert!(preferred.score(&summary(4)) > preferred.score(&software));
+
+        let any = ConfigRequirements {
+            hardware_acceleration: HardwareAcceleration::Any,
+            ..ConfigRequirements::default()
+        };
+        assert_eq!(any.score(&summary(4)), any.score(&software));
+    }
+
+    #[test]
+    fn prefers_exact_samples_then_more_over_fewer() {
+        let requirements = ConfigRequirements::default();
+        let candidates = [0, 2, 4, 8, 16].map(|samples| (summary(samples), samples));
+        assert_eq!(requirements.best(candidates), Some(4));
+        let candidates = [0, 2, 8].map(|samples| (summary(samples), samples));
+        assert_eq!(requirements.best(candidates), Some(8));
+    }
+
+    #[test]
+    fn prefers_the_least_wasted_bits() {
+        let requirements = ConfigRequirements::default();
+        let candidates = [32, 24, 0].map(|depth_bits| {
+            let config = ConfigSummary {
+                depth_bits,
+                ..summary(4)
+            };
+            (config, depth_bits)
+        });
+        assert_eq!(requirements.best(candidates), Some(0));
+    }
+
+    #[test]
+    fn picks_nothing_when_nothing_qualifies() {
+        let requirements = ConfigRequirements {
+            alpha_bits: 16,
+            ..ConfigRequirements::default()
+        };
+        assert_eq!(requirements.best([(summary(4), ())]), None);
+        assert_eq!(requirements.best(Vec::<(ConfigSummary, ())>::new()), None);
+    }
+}
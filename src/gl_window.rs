@@ -0,0 +1,548 @@
+//! Window + OpenGL context bootstrap shared by the glow based demos.
+//!
+//! Every GL demo needs the same dance: let glutin pick a framebuffer config and
+//! create the winit window for it, build a window surface, create a context,
+//! make it current and finally load the GL function pointers into glow.
+//! [`GlWindowBuilder`] does all of that once and hands back a [`GlWindow`].
+//!
+//! The framebuffer config is chosen by [`ConfigRequirements`]. Context
+//! creation walks a chain of [`GlApi`]s, by default [`GlApi::FALLBACK_CHAIN`],
+//! so the demos still start on drivers and virtual machines that only expose
+//! GLES or an older desktop GL.
+
+use std::error::Error;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use glow::HasContext;
+use glutin::config::{Config, ConfigTemplateBuilder};
+use glutin::context::{
+    ContextApi, ContextAttributesBuilder, GlProfile, PossiblyCurrentContext, Version,
+};
+use glutin::display::GetGlDisplay;
+use glutin::prelude::*;
+use glutin::surface::{Surface, SurfaceAttributesBuilder, SwapInterval, WindowSurface};
+use glutin_winit::{DisplayBuilder, GlWindow as _};
+use winit::dpi::PhysicalSize;
+use winit::event_loop::ActiveEventLoop;
+use winit::raw_window_handle::HasWindowHandle;
+use winit::window::{Window, WindowAttributes};
+
+use crate::gl_config::{ConfigRequirements, ConfigSummary};
+
+/// A winit window together with a current OpenGL context rendering into it.
+pub struct GlWindow {
+    pub window: Window,
+    pub gl_config: Config,
+    pub gl_context: PossiblyCurrentContext,
+    pub gl_surface: Surface<WindowSurface>,
+    pub gl: Arc<glow::Context>,
+    /// The API the context was created with.
+    pub api: GlApi,
+}
+
+impl GlWindow {
+    /// Resize the GL surface to match the window, ignoring zero sized (minimized) windows.
+    ///
+    /// Returns `true` when the surface was actually resized.
+    pub fn resize(&self, size: PhysicalSize<u32>) -> bool {
+        match (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) {
+            (Some(width), Some(height)) => {
+                self.gl_surface.resize(&self.gl_context, width, height);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Present the back buffer.
+    pub fn swap_buffers(&self) -> Result<(), glutin::error::Error> {
+        self.gl_surface.swap_buffers(&self.gl_context)
+    }
+
+    /// Set the swap interval, falling back along [`VsyncMode::fallbacks`] when
+    /// the driver refuses a mode.
+    ///
+    /// Returns the mode now in effect, or `None` when nothing could be set and
+    /// the driver default applies.
+    pub fn set_vsync(&self, requested: VsyncMode) -> Option<VsyncMode> {
+        for &mode in requested.fallbacks() {
+            let Some(interval) = mode.swap_interval() else {
+                log::warn!("{mode} is not supported by glutin, falling back");
+                continue;
+            };
+            match self
+                .gl_surface
+                .set_swap_interval(&self.gl_context, interval)
+            {
+                Ok(()) => {
+                    log::info!("Swap interval set to {mode}");
+                    return Some(mode);
+                }
+                Err(err) => log::warn!("Failed to set swap interval to {mode}: {err}"),
+            }
+        }
+        None
+    }
+}
+
+/// How buffer swaps are synchronized to the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VsyncMode {
+    /// Wait for every vertical blank.
+    #[default]
+    Vsync,
+    /// Vsync, but swap immediately when a frame is late instead of waiting
+    /// for the next blank (`EXT_swap_control_tear`).
+    Adaptive,
+    /// Never wait; frames may tear.
+    Immediate,
+}
+
+impl VsyncMode {
+    pub const ALL: [VsyncMode; 3] = [Self::Vsync, Self::Adaptive, Self::Immediate];
+
+    /// The modes [`GlWindow::set_vsync`] tries, in order, when this one is requested.
+    pub fn fallbacks(self) -> &'static [VsyncMode] {
+        match self {
+            Self::Vsync => &[Self::Vsync],
+            Self::Adaptive => &[Self::Adaptive, Self::Vsync],
+            Self::Immediate => &[Self::Immediate],
+        }
+    }
+
+    /// The glutin swap interval for this mode.
+    ///
+    /// glutin has no way to ask for the negative interval adaptive vsync
+    /// needs, so that mode always falls back.
+    fn swap_interval(self) -> Option<SwapInterval> {
+        match self {
+            Self::Vsync => Some(SwapInterval::Wait(NonZeroU32::MIN)),
+            Self::Adaptive => None,
+            Self::Immediate => Some(SwapInterval::DontWait),
+        }
+    }
+}
+
+impl FromStr for VsyncMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "on" | "vsync" => Ok(Self::Vsync),
+            "adaptive" => Ok(Self::Adaptive),
+            "off" | "immediate" => Ok(Self::Immediate),
+            _ => Err(format!(
+                "invalid vsync mode: {s} (expected on, adaptive or off)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for VsyncMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Vsync => "vsync",
+            Self::Adaptive => "adaptive",
+            Self::Immediate => "immediate",
+        })
+    }
+}
+
+/// An OpenGL or OpenGL ES version a context can be requested for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlApi {
+    /// Desktop OpenGL, core profile from 3.2 on.
+    Gl(u8, u8),
+    /// OpenGL ES.
+    Gles(u8, u8),
+}
+
+impl GlApi {
+    /// The order in which [`GlWindowBuilder`] tries APIs unless told otherwise.
+    pub const FALLBACK_CHAIN: [GlApi; 4] = [
+        GlApi::Gl(4, 1),
+        GlApi::Gl(3, 3),
+        GlApi::Gles(3, 0),
+        GlApi::Gles(2, 0),
+    ];
+
+    /// Whether the demos' shaders can be built for this API. They are written
+    /// with GLSL 1.30 `in`/`out` variables, which GLSL ES 1.00 (OpenGL ES 2)
+    /// gets mapped onto by [`ShaderProgram::new`](crate::shader::ShaderProgram::new),
+    /// but GLSL 1.10/1.20 (OpenGL 2) does not.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Self::Gl(major, _) => major >= 3,
+            Self::Gles(major, _) => major >= 2,
+        }
+    }
+
+    /// Map the version reported by a live context onto an API, for contexts
+    /// created elsewhere (e.g. by eframe).
+    pub fn from_context(gl: &glow::Context) -> Self {
+        let version = gl.version();
+        let (major, minor) = (version.major as u8, version.minor as u8);
+        if version.is_embedded {
+            Self::Gles(major, minor)
+        } else {
+            Self::Gl(major, minor)
+        }
+    }
+
+    /// The `#version` line matching this API, to pass to
+    /// [`ShaderProgram::new`](crate::shader::ShaderProgram::new).
+    ///
+    /// Covers OpenGL 2 too, for contexts created elsewhere, but see
+    /// [`GlApi::is_supported`].
+    pub fn glsl_version_header(self) -> String {
+        match self {
+            Self::Gl(major, minor) => {
+                let glsl = match (major, minor) {
+                    (2, 0) => 110,
+                    (2, 1) => 120,
+                    (3, 0) => 130,
+                    (3, 1) => 140,
+                    (3, 2) => 150,
+                    (major, minor) => u32::from(major) * 100 + u32::from(minor) * 10,
+                };
+                format!("#version {glsl}")
+            }
+            Self::Gles(2, _) => "#version 100".to_owned(),
+            Self::Gles(major, minor) => format!("#version {major}{minor}0 es"),
+        }
+    }
+
+    /// Apply this API's version and profile to a context attributes builder.
+    pub(crate) fn context_attributes(
+        self,
+        builder: ContextAttributesBuilder,
+    ) -> ContextAttributesBuilder {
+        let builder = builder.with_context_api(self.context_api());
+        match self.profile() {
+            Some(profile) => builder.with_profile(profile),
+            None => builder,
+        }
+    }
+
+    fn context_api(self) -> ContextApi {
+        match self {
+            Self::Gl(major, minor) => ContextApi::OpenGl(Some(Version::new(major, minor))),
+            Self::Gles(major, minor) => ContextApi::Gles(Some(Version::new(major, minor))),
+        }
+    }
+
+    fn profile(self) -> Option<GlProfile> {
+        match self {
+            Self::Gl(major, minor) if (major, minor) >= (3, 2) => Some(GlProfile::Core),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for GlApi {
+    type Err = String;
+
+    /// `gl<major>.<minor>` or `gles<major>.<minor>`, e.g. `gl3.3` or `gles3.0`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid GL API: {s} (expected e.g. gl4.1, gl3.3 or gles3.0)");
+        let (gles, version) = match s.strip_prefix("gles") {
+            Some(version) => (true, version),
+            None => (false, s.strip_prefix("gl").ok_or_else(invalid)?),
+        };
+        let (major, minor) = version.split_once('.').ok_or_else(invalid)?;
+        let (major, minor) = (
+            major.parse().map_err(|_| invalid())?,
+            minor.parse().map_err(|_| invalid())?,
+        );
+        if major == 0 {
+            return Err(invalid());
+        }
+        let api = if gles {
+            Self::Gles(major, minor)
+        } else {
+            Self::Gl(major, minor)
+        };
+        if !api.is_supported() {
+            return Err(format!(
+                "unsupported GL API: {s} (the shaders need OpenGL 3.0 or OpenGL ES 2.0)"
+            ));
+        }
+        Ok(api)
+    }
+}
+
+impl fmt::Display for GlApi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gl(major, minor) if self.profile().is_some() => {
+                write!(f, "OpenGL {major}.{minor} core")
+            }
+            Self::Gl(major, minor) => write!(f, "OpenGL {major}.{minor}"),
+            Self::Gles(major, minor) => write!(f, "OpenGL ES {major}.{minor}"),
+        }
+    }
+}
+
+/// Builder for [`GlWindow`].
+pub struct GlWindowBuilder {
+    window_attributes: WindowAttributes,
+    config_requirements: ConfigRequirements,
+    apis: Vec<GlApi>,
+}
+
+impl GlWindowBuilder {
+    pub fn new(window_attributes: WindowAttributes) -> Self {
+        Self {
+            window_attributes,
+            config_requirements: ConfigRequirements::default(),
+            apis: GlApi::FALLBACK_CHAIN.to_vec(),
+        }
+    }
+
+    /// Requirements the framebuffer config has to meet.
+    pub fn with_config_requirements(mut self, requirements: ConfigRequirements) -> Self {
+        self.config_requirements = requirements;
+        self
+    }
+
+    /// APIs to try, in order, when creating the context.
+    pub fn with_apis(mut self, apis: impl IntoIterator<Item = GlApi>) -> Self {
+        self.apis = apis.into_iter().collect();
+        self
+    }
+
+    pub fn build(self, event_loop: &ActiveEventLoop) -> Result<GlWindow, GlWindowError> {
+        let requirements = self.config_requirements;
+        let template = ConfigTemplateBuilder::new().with_transparency(requirements.transparency);
+
+        let display_builder =
+            DisplayBuilder::new().with_window_attributes(Some(self.window_attributes));
+
+        // The picker has to return some config, so when nothing qualifies it
+        // remembers what was on offer and the error is raised after `build`.
+        let mut rejected = None;
+        let (window, gl_config) = display_builder
+            .build(event_loop, template, |configs| {
+                let configs: Vec<_> = configs.collect();
+                match requirements.pick(&configs) {
+                    Some(config) => config.clone(),
+                    None => {
+                        rejected = Some(configs.iter().map(ConfigSummary::from).collect());
+                        configs
+                            .into_iter()
+                            .next()
+                            .expect("glutin yields at least one config")
+                    }
+                }
+            })
+            .map_err(GlWindowError::Display)?;
+
+        if let Some(available) = rejected {
+            return Err(GlWindowError::NoMatchingConfig {
+                requirements,
+                available,
+            });
+        }
+        log::info!("Picked GL config: {}", ConfigSummary::from(&gl_config));
+
+        let window = window.ok_or(GlWindowError::NoWindow)?;
+        let raw_window_handle = window.window_handle().ok().map(|h| h.as_raw());
+        let gl_display = gl_config.display();
+
+        let surface_attributes =
+            SurfaceAttributesBuilder::new().with_srgb(requirements.srgb.then_some(true));
+        let attrs = window
+            .build_surface_attributes(surface_attributes)
+            .map_err(GlWindowError::SurfaceAttributes)?;
+        let gl_surface = unsafe { gl_display.create_window_surface(&gl_config, &attrs)? };
+
+        let mut failures = Vec::new();
+        let mut current = None;
+        for &api in &self.apis {
+            let context_attributes = api
+                .context_attributes(ContextAttributesBuilder::new())
+                .build(raw_window_handle);
+
+            // Some drivers hand out a context they cannot make current, so
+            // both steps count towards deciding whether an API works.
+            let result = unsafe { gl_display.create_context(&gl_config, &context_attributes) }
+                .and_then(|context| context.make_current(&gl_surface));
+            match result {
+                Ok(context) => {
+                    current = Some((api, context));
+                    break;
+                }
+                Err(err) => {
+                    log::warn!("{api} context not available: {err}");
+                    failures.push((api, err));
+                }
+            }
+        }
+        let (api, gl_context) = current.ok_or(GlWindowError::NoContext(failures))?;
+
+        let gl = Arc::new(unsafe {
+            glow::Context::from_loader_function_cstr(|s| gl_display.get_proc_address(s).cast())
+        });
+
+        log::info!("Created {api} context: {}", unsafe {
+            gl.get_parameter_string(glow::VERSION)
+        });
+
+        Ok(GlWindow {
+            window,
+            gl_config,
+            gl_context,
+            gl_surface,
+            gl,
+            api,
+        })
+    }
+}
+
+/// Reasons [`GlWindowBuilder::build`] can fail.
+#[derive(Debug)]
+pub enum GlWindowError {
+    /// Creating the GL display, picking a config or creating the window failed.
+    Display(Box<dyn Error>),
+    /// No framebuffer config met the requirements.
+    NoMatchingConfig {
+        requirements: ConfigRequirements,
+        available: Vec<ConfigSummary>,
+    },
+    /// glutin did not create a window even though window attributes were given.
+    NoWindow,
+    /// The window handle could not be turned into surface attributes.
+    SurfaceAttributes(winit::raw_window_handle::HandleError),
+    /// Surface creation failed.
+    Glutin(glutin::error::Error),
+    /// None of the requested APIs produced a usable context.
+    NoContext(Vec<(GlApi, glutin::error::Error)>),
+}
+
+impl fmt::Display for GlWindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Display(err) => write!(f, "failed to create GL display and window: {err}"),
+            Self::NoMatchingConfig {
+                requirements,
+                available,
+            } => {
+                write!(
+                    f,
+                    "no GL config matches {requirements:?}, available configs:"
+                )?;
+                for config in available {
+                    write!(f, "\n  {config}")?;
+                }
+                Ok(())
+            }
+            Self::NoWindow => write!(f, "no window was created for the GL display"),
+            Self::SurfaceAttributes(err) => write!(f, "failed to get window handle: {err}"),
+            Self::Glutin(err) => write!(f, "failed to create GL surface: {err}"),
+            Self::NoContext(failures) => {
+                write!(f, "failed to create a GL context")?;
+                for (api, err) in failures {
+                    write!(f, "\n  {api}: {err}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for GlWindowError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Display(err) => Some(err.as_ref()),
+            Self::NoMatchingConfig { .. } | Self::NoWindow => None,
+            Self::SurfaceAttributes(err) => Some(err),
+            Self::Glutin(err) => Some(err),
+            Self::NoContext(_) => None,
+        }
+    }
+}
+
+impl From<glutin::error::Error> for GlWindowError {
+    fn from(err: glutin::error::Error) -> Self {
+        Self::Glutin(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gl_apis() {
+        assert_eq!("gl4.1".parse(), Ok(GlApi::Gl(4, 1)));
+        assert_eq!("gl3.0".parse(), Ok(GlApi::Gl(3, 0)));
+        assert_eq!("gles3.2".parse(), Ok(GlApi::Gles(3, 2)));
+        assert_eq!("gles2.0".parse(), Ok(GlApi::Gles(2, 0)));
+    }
+
+    #[test]
+    fn rejects_gl_apis_the_shaders_cannot_use() {
+        for api in ["gl2.1", "gl1.0", "gles1.1"] {
+            let err = api.parse::<GlApi>().unwrap_err();
+            assert!(err.starts_with("unsupported GL API"), "{api}: {err}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_gl_apis() {
+        for api in [
+            "",
+            "gl",
+            "gl4",
+            "gl0.0",
+            "gl4.x",
+            "gles",
+            "vulkan1.3",
+            "GL4.1",
+            "gl4.1 ",
+        ] {
+            let err = api.parse::<GlApi>().unwrap_err();
+            assert!(err.starts_with("invalid GL API"), "{api}: {err}");
+        }
+    }
+
+    #[test]
+    fn fallback_chain_is_supported() {
+        assert!(GlApi::FALLBACK_CHAIN.iter().all(|api| api.is_supported()));
+    }
+
+    #[test]
+    fn parses_vsync_modes() {
+        assert_eq!("on".parse(), Ok(VsyncMode::Vsync));
+        assert_eq!("vsync".parse(), Ok(VsyncMode::Vsync));
+        assert_eq!("off".parse(), Ok(VsyncMode::Immediate));
+        assert_eq!("immediate".parse(), Ok(VsyncMode::Immediate));
+        assert_eq!("adaptive".parse(), Ok(VsyncMode::Adaptive));
+        for mode in ["", "tear", "On", "1"] {
+            assert!(mode.parse::<VsyncMode>().is_err(), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn vsync_modes_fall_back_to_vsync() {
+        // Each mode is tried first, and only adaptive has somewhere to go
+        for mode in VsyncMode::ALL {
+            assert_eq!(mode.fallbacks().first(), Some(&mode));
+        }
+        assert_eq!(
+            VsyncMode::Adaptive.fallbacks(),
+            [VsyncMode::Adaptive, VsyncMode::Vsync]
+        );
+        assert_eq!(VsyncMode::Immediate.fallbacks(), [VsyncMode::Immediate]);
+    }
+
+    #[test]
+    fn vsync_modes_round_trip_through_display() {
+        for mode in VsyncMode::ALL {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+        }
+    }
+}
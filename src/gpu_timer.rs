@@ -0,0 +1,292 @@
+//! GPU-side timing of render passes with `GL_TIME_ELAPSED` query objects.
+//!
+//! CPU timers around `draw_arrays` only measure how long it takes to queue the
+//! commands; the GPU runs them later. A query brackets the commands on the GPU
+//! instead, but its result is only ready a frame or two after it was issued.
+//! Waiting for it would stall the pipeline, so each frame gets its own set of
+//! queries from a small ring and results are picked up once the driver reports
+//! them available.
+//!
+//! Timer queries need OpenGL 3.3, `GL_ARB_timer_query` or, on OpenGL ES,
+//! `GL_EXT_disjoint_timer_query`. Without them the timer is disabled and every
+//! call is a no-op.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use glow::HasContext;
+
+/// How many frames of queries can be in flight before a slot is reused.
+const FRAMES_IN_FLIGHT: usize = 4;
+
+/// How often the latest GPU times are written to the log.
+const LOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `GL_GPU_DISJOINT_EXT`, which glow doesn't export.
+const GPU_DISJOINT_EXT: u32 = 0x8FBB;
+
+/// A render pass timed on the GPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuPass {
+    /// The app's own scene: clearing and drawing the triangle.
+    Scene,
+    /// Painting egui's primitives.
+    Egui,
+}
+
+impl GpuPass {
+    pub const ALL: [GpuPass; 2] = [Self::Scene, Self::Egui];
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Scene => "GPU scene",
+            Self::Egui => "GPU egui",
+        }
+    }
+}
+
+/// The newest GPU time of each pass, indexed like [`GpuPass::ALL`].
+pub type GpuTimes = [Option<Duration>; GpuPass::ALL.len()];
+
+/// One frame's worth of queries.
+struct Slot {
+    queries: [glow::Query; GpuPass::ALL.len()],
+    /// Passes whose query was issued and whose result hasn't been read yet.
+    pending: [bool; GpuPass::ALL.len()],
+}
+
+/// Times [`GpuPass`]es with a ring of `GL_TIME_ELAPSED` queries.
+pub struct GpuTimer {
+    gl: Arc<glow::Context>,
+    /// Empty when timer queries are unsupported.
+    slots: Vec<Slot>,
+    /// The slot used by the current frame.
+    current: usize,
+    /// The pass whose query is open; `GL_TIME_ELAPSED` queries can't nest.
+    active: Option<GpuPass>,
+    /// Whether results can be invalidated by `GL_GPU_DISJOINT_EXT` (OpenGL ES).
+    check_disjoint: bool,
+    latest: GpuTimes,
+    last_log: Option<Instant>,
+}
+
+impl GpuTimer {
+    /// Create the query ring, or a disabled timer when the context lacks
+    /// timer query support.
+    pub fn new(gl: Arc<glow::Context>) -> Self {
+        let mut timer = Self {
+            gl,
+            slots: Vec::new(),
+            current: 0,
+            active: None,
+            check_disjoint: false,
+            latest: [None; GpuPass::ALL.len()],
+            last_log: None,
+        };
+
+        match timer_query_support(&timer.gl) {
+            Ok(check_disjoint) => {
+                timer.check_disjoint = check_disjoint;
+                match timer.create_slots() {
+                    Ok(slots) => timer.slots = slots,
+                    Err(err) => log::warn!("GPU timing disabled: failed to create queries: {err}"),
+                }
+            }
+            Err(reason) => log::info!("GPU timing disabled: {reason}"),
+        }
+        timer
+    }
+
+    fn create_slots(&self) -> Result<Vec<Slot>, String> {
+        let mut slots = Vec::with_capacity(FRAMES_IN_FLIGHT);
+        for _ in 0..FRAMES_IN_FLIGHT {
+            let mut queries = Vec::with_capacity(GpuPass::ALL.len());
+            for _ in GpuPass::ALL {
+                match unsafe { self.gl.create_query() } {
+                    Ok(query) => queries.push(query),
+                    Err(err) => {
+                        let created = slots.iter().flat_map(|slot: &Slot| slot.queries);
+                        for query in created.chain(queries) {
+                            unsafe { self.gl.delete_query(query) };
+                        }
+                        return Err(err);
+                    }
+                }
+            }
+            slots.push(Slot {
+                queries: queries.try_into().expect("one query per pass"),
+                pending: [false; GpuPass::ALL.len()],
+            });
+        }
+        Ok(slots)
+    }
+
+    /// Whether timer queries are supported and passes are actually timed.
+    pub fn is_enabled(&self) -> bool {
+        !self.slots.is_empty()
+    }
+
+    /// Collect whatever results have become available and move on to the next
+    /// slot in the ring. Call once per frame before the first [`GpuTimer::begin`].
+    ///
+    /// Returns the newest time of each pass that finished since the last call;
+    /// these lag the CPU by as many frames as the driver buffers.
+    pub fn begin_frame(&mut self) -> GpuTimes {
+        let mut finished = [None; GpuPass::ALL.len()];
+        if !self.is_enabled() {
+            return finished;
+        }
+        if let Some(pass) = self.active.take() {
+            log::warn!("GPU timer query for {pass:?} was never ended");
+            unsafe { self.gl.end_query(glow::TIME_ELAPSED) };
+        }
+
+        // Walk from the oldest slot to the newest so later frames win
+        let count = self.slots.len();
+        for offset in 1..=count {
+            let index = (self.current + offset) % count;
+            for pass in GpuPass::ALL {
+                if let Some(time) = self.read_result(index, pass) {
+                    finished[pass.index()] = Some(time);
+                }
+            }
+        }
+
+        // A disjoint event (e.g. a GPU frequency change) makes every result
+        // collected since the last check meaningless
+        if self.check_disjoint && unsafe { self.gl.get_parameter_i32(GPU_DISJOINT_EXT) } != 0 {
+            finished = [None; GpuPass::ALL.len()];
+        }
+
+        for (latest, time) in self.latest.iter_mut().zip(finished) {
+            if time.is_some() {
+                *latest = time;
+            }
+        }
+        self.log_latest();
+
+        self.current = (self.current + 1) % count;
+        finished
+    }
+
+    /// Read the result of `pass` in slot `index` if it is ready, without blocking.
+    fn read_result(&mut self, index: usize, pass: GpuPass) -> Option<Duration> {
+        let slot = &mut self.slots[index];
+        if !slot.pending[pass.index()] {
+            return None;
+        }
+        let query = slot.queries[pass.index()];
+
+        // glow's u32 getter only uses the core entry point when GL 4.5 is
+        // present, so read everything through the 64-bit one, which is core
+        // since 3.3. With no query buffer bound the "offset" is a pointer.
+        let read = |parameter: u32| {
+            let mut value: u64 = 0;
+            unsafe {
+                self.gl.get_query_parameter_u64_with_offset(
+                    query,
+                    parameter,
+                    &mut value as *mut u64 as usize,
+                );
+            }
+            value
+        };
+
+        if read(glow::QUERY_RESULT_AVAILABLE) == 0 {
+            return None;
+        }
+        slot.pending[pass.index()] = false;
+        Some(Duration::from_nanos(read(glow::QUERY_RESULT)))
+    }
+
+    /// Start timing `pass`. Ends the previous pass if it is still open.
+    pub fn begin(&mut self, pass: GpuPass) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.end();
+
+        // Non-blocking: if this slot's result from FRAMES_IN_FLIGHT frames ago
+        // still isn't in, skip timing the pass this frame instead of waiting
+        let slot = &mut self.slots[self.current];
+        if slot.pending[pass.index()] {
+            return;
+        }
+        unsafe {
+            self.gl
+                .begin_query(glow::TIME_ELAPSED, slot.queries[pass.index()])
+        };
+        slot.pending[pass.index()] = true;
+        self.active = Some(pass);
+    }
+
+    /// Stop timing the pass started by [`GpuTimer::begin`].
+    pub fn end(&mut self) {
+        if self.active.take().is_some() {
+            unsafe { self.gl.end_query(glow::TIME_ELAPSED) };
+        }
+    }
+
+    /// The most recent GPU time measured for `pass`.
+    pub fn latest(&self, pass: GpuPass) -> Option<Duration> {
+        self.latest[pass.index()]
+    }
+
+    fn log_latest(&mut self) {
+        let now = Instant::now();
+        if self
+            .last_log
+            .is_some_and(|last_log| now - last_log < LOG_INTERVAL)
+            || self.latest.iter().all(Option::is_none)
+        {
+            return;
+        }
+        self.last_log = Some(now);
+
+        let times = GpuPass::ALL
+            .iter()
+            .filter_map(|&pass| {
+                let time = self.latest(pass)?;
+                Some(format!(
+                    "{} {:.3} ms",
+                    pass.label(),
+                    time.as_secs_f64() * 1000.0
+                ))
+            })
+            .collect::<Vec<_>>();
+        log::debug!("{}", times.join(", "));
+    }
+}
+
+impl Drop for GpuTimer {
+    fn drop(&mut self) {
+        self.end();
+        for slot in &self.slots {
+            for &query in &slot.queries {
+                unsafe { self.gl.delete_query(query) };
+            }
+        }
+    }
+}
+
+/// `Ok(check_disjoint)` when `gl` supports `GL_TIME_ELAPSED` queries, or why not.
+fn timer_query_support(gl: &glow::Context) -> Result<bool, &'static str> {
+    let version = gl.version();
+    let extensions = gl.supported_extensions();
+    if version.is_embedded {
+        if extensions.contains("GL_EXT_disjoint_timer_query") {
+            Ok(true)
+        } else {
+            Err("OpenGL ES needs GL_EXT_disjoint_timer_query")
+        }
+    } else if (version.major, version.minor) >= (3, 3) || extensions.contains("GL_ARB_timer_query")
+    {
+        Ok(false)
+    } else {
+        Err("needs OpenGL 3.3 or GL_ARB_timer_query")
+    }
+}
@@ -0,0 +1,42 @@
+//! Offscreen GL rendering without a window, for CI machines without a GPU.
+//!
+//! The demos accept `--headless [--frames N] [--output PATH]`, parsed into
+//! [`HeadlessOptions`] by [`crate::cli`], and render through a
+//! [`HeadlessContext`] instead of a window. Rendering is driven with a fixed
+//! timestep so output is reproducible.
+
+use std::path::PathBuf;
+
+#[cfg(not(target_vendor = "apple"))]
+mod egl;
+
+#[cfg(not(target_vendor = "apple"))]
+pub use egl::{HeadlessContext, HeadlessError};
+
+/// Options for `--headless` mode, which renders offscreen and writes a PNG
+/// instead of opening a window. Parsed by [`CommonArgs`](crate::cli::CommonArgs).
+#[derive(Debug, Clone)]
+pub struct HeadlessOptions {
+    /// The size of the image, from `--size`.
+    pub size: [u32; 2],
+    pub frames: u32,
+    pub output: PathBuf,
+}
+
+/// egui input for frame number `frame` of a headless run.
+///
+/// Time advances at a fixed 60 Hz so animations, and therefore the rendered
+/// images, are the same on every run regardless of how long a frame takes.
+pub fn egui_input(frame: u32, [width, height]: [u32; 2]) -> egui::RawInput {
+    const DT: f32 = 1.0 / 60.0;
+    egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(width as f32, height as f32),
+        )),
+        time: Some(f64::from(frame) * f64::from(DT)),
+        predicted_dt: DT,
+        focused: true,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,258 @@
+//! The EGL backed offscreen context.
+//!
+//! The context comes from an EGL device display, which Mesa provides even with
+//! no display server running (llvmpipe). It is made current without a surface
+//! when `EGL_KHR_surfaceless_context` is available and with a small pbuffer
+//! otherwise; either way rendering goes into a framebuffer object owned by
+//! [`HeadlessContext`], so the demos' draw code runs unchanged.
+
+use std::error::Error;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use glow::HasContext;
+use glutin::api::egl::config::Config;
+use glutin::api::egl::context::PossiblyCurrentContext;
+use glutin::api::egl::device::Device;
+use glutin::api::egl::display::Display;
+use glutin::api::egl::surface::Surface;
+use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+use glutin::context::{ContextAttributes, ContextAttributesBuilder};
+use glutin::prelude::*;
+use glutin::surface::{PbufferSurface, SurfaceAttributesBuilder};
+use image::RgbaImage;
+
+use crate::capture::read_framebuffer;
+use crate::gl_window::GlApi;
+
+/// A current GL context rendering into an offscreen framebuffer.
+pub struct HeadlessContext {
+    pub gl: Arc<glow::Context>,
+    /// The API the context was created with.
+    pub api: GlApi,
+    size: [u32; 2],
+    framebuffer: glow::Framebuffer,
+    renderbuffers: [glow::Renderbuffer; 2],
+    // Kept alive for as long as the context renders; dropped after it.
+    _gl_context: PossiblyCurrentContext,
+    _pbuffer: Option<Surface<PbufferSurface>>,
+}
+
+impl HeadlessContext {
+    /// Create a `width` x `height` offscreen target, trying the APIs of
+    /// [`GlApi::FALLBACK_CHAIN`] in order.
+    pub fn new(width: u32, height: u32) -> Result<Self, HeadlessError> {
+        Self::with_apis(width, height, &GlApi::FALLBACK_CHAIN)
+    }
+
+    /// Like [`HeadlessContext::new`], trying `apis` in order instead.
+    pub fn with_apis(width: u32, height: u32, apis: &[GlApi]) -> Result<Self, HeadlessError> {
+        let (Some(pbuffer_width), Some(pbuffer_height)) =
+            (NonZeroU32::new(width), NonZeroU32::new(height))
+        else {
+            return Err(HeadlessError::ZeroSize);
+        };
+
+        let display = Device::query_devices()
+            .map_err(HeadlessError::Egl)?
+            .find_map(|device| unsafe { Display::with_device(&device, None) }.ok())
+            .ok_or(HeadlessError::NoDevice)?;
+
+        let template = ConfigTemplateBuilder::new()
+            .with_alpha_size(8)
+            .with_surface_type(ConfigSurfaceTypes::PBUFFER)
+            .build();
+        let config = unsafe { display.find_configs(template) }
+            .map_err(HeadlessError::Egl)?
+            .next()
+            .ok_or(HeadlessError::NoConfig)?;
+
+        let mut failures = Vec::new();
+        let mut current = None;
+        for &api in apis {
+            let context_attributes = api
+                .context_attributes(ContextAttributesBuilder::new())
+                .build(None);
+            match make_current(
+                &display,
+                &config,
+                &context_attributes,
+                [pbuffer_width, pbuffer_height],
+            ) {
+                Ok((context, pbuffer)) => {
+                    current = Some((api, context, pbuffer));
+                    break;
+                }
+                Err(err) => {
+                    log::warn!("{api} context not available: {err}");
+                    failures.push((api, err));
+                }
+            }
+        }
+        let (api, gl_context, pbuffer) = current.ok_or(HeadlessError::NoContext(failures))?;
+
+        let gl = Arc::new(unsafe {
+            glow::Context::from_loader_function_cstr(|s| display.get_proc_address(s).cast())
+        });
+        log::info!("Created headless {api} context: {}", unsafe {
+            gl.get_parameter_string(glow::VERSION)
+        });
+
+        let (framebuffer, renderbuffers) = unsafe {
+            create_framebuffer(&gl, width as i32, height as i32).map_err(HeadlessError::Gl)?
+        };
+
+        Ok(Self {
+            gl,
+            api,
+            size: [width, height],
+            framebuffer,
+            renderbuffers,
+            _gl_context: gl_context,
+            _pbuffer: pbuffer,
+        })
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    /// Bind the offscreen framebuffer. Done once on creation; call it again
+    /// if the render code binds a different framebuffer.
+    pub fn bind(&self) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+        }
+    }
+
+    /// Read back what has been rendered so far.
+    pub fn read_pixels(&self) -> RgbaImage {
+        self.bind();
+        unsafe { self.gl.finish() };
+        read_framebuffer(&self.gl, self.size)
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_framebuffer(self.framebuffer);
+            for renderbuffer in self.renderbuffers {
+                self.gl.delete_renderbuffer(renderbuffer);
+            }
+        }
+    }
+}
+
+/// Create a context and make it current, surfaceless if the driver allows it.
+fn make_current(
+    display: &Display,
+    config: &Config,
+    attributes: &ContextAttributes,
+    [width, height]: [NonZeroU32; 2],
+) -> Result<(PossiblyCurrentContext, Option<Surface<PbufferSurface>>), glutin::error::Error> {
+    let context = unsafe { display.create_context(config, attributes)? };
+    match context.make_current_surfaceless() {
+        Ok(context) => return Ok((context, None)),
+        Err(err) => log::info!("Surfaceless context unavailable ({err}), using a pbuffer"),
+    }
+
+    // A failed make_current consumes the context, so start over with a new one
+    let context = unsafe { display.create_context(config, attributes)? };
+    let attrs = SurfaceAttributesBuilder::<PbufferSurface>::new().build(width, height);
+    let pbuffer = unsafe { display.create_pbuffer_surface(config, &attrs)? };
+    let context = context.make_current(&pbuffer)?;
+    Ok((context, Some(pbuffer)))
+}
+
+unsafe fn create_framebuffer(
+    gl: &glow::Context,
+    width: i32,
+    height: i32,
+) -> Result<(glow::Framebuffer, [glow::Renderbuffer; 2]), String> {
+    unsafe {
+        let color = gl.create_renderbuffer()?;
+        gl.bind_renderbuffer(glow::RENDERBUFFER, Some(color));
+        gl.renderbuffer_storage(glow::RENDERBUFFER, glow::RGBA8, width, height);
+
+        let depth_stencil = gl.create_renderbuffer().inspect_err(|_| {
+            gl.delete_renderbuffer(color);
+        })?;
+        gl.bind_renderbuffer(glow::RENDERBUFFER, Some(depth_stencil));
+        gl.renderbuffer_storage(glow::RENDERBUFFER, glow::DEPTH24_STENCIL8, width, height);
+
+        let framebuffer = gl.create_framebuffer().inspect_err(|_| {
+            gl.delete_renderbuffer(color);
+            gl.delete_renderbuffer(depth_stencil);
+        })?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+        gl.framebuffer_renderbuffer(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::RENDERBUFFER,
+            Some(color),
+        );
+        gl.framebuffer_renderbuffer(
+            glow::FRAMEBUFFER,
+            glow::DEPTH_STENCIL_ATTACHMENT,
+            glow::RENDERBUFFER,
+            Some(depth_stencil),
+        );
+
+        let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            gl.delete_framebuffer(framebuffer);
+            gl.delete_renderbuffer(color);
+            gl.delete_renderbuffer(depth_stencil);
+            return Err(format!("framebuffer incomplete: {status:#x}"));
+        }
+
+        Ok((framebuffer, [color, depth_stencil]))
+    }
+}
+
+/// Reasons [`HeadlessContext::new`] can fail.
+#[derive(Debug)]
+pub enum HeadlessError {
+    /// Width or height was zero.
+    ZeroSize,
+    /// No EGL device could be opened as a display.
+    NoDevice,
+    /// The display offers no pbuffer capable RGBA config.
+    NoConfig,
+    /// None of the APIs in the fallback chain produced a current context.
+    NoContext(Vec<(GlApi, glutin::error::Error)>),
+    Egl(glutin::error::Error),
+    /// Creating the offscreen framebuffer failed.
+    Gl(String),
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroSize => write!(f, "headless render target must not be empty"),
+            Self::NoDevice => write!(f, "no EGL device available for headless rendering"),
+            Self::NoConfig => write!(f, "no EGL config supports offscreen rendering"),
+            Self::NoContext(failures) => {
+                write!(f, "failed to create a headless GL context")?;
+                for (api, err) in failures {
+                    write!(f, "\n  {api}: {err}")?;
+                }
+                Ok(())
+            }
+            Self::Egl(err) => write!(f, "EGL error: {err}"),
+            Self::Gl(err) => write!(f, "failed to create offscreen framebuffer: {err}"),
+        }
+    }
+}
+
+impl Error for HeadlessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Egl(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Shared building blocks for the winit + glutin + glow + egui demos run by
+//! the `winit-test` launcher.
+
+pub mod assets;
+pub mod camera;
+pub mod capture;
+pub mod cli;
+pub mod frame_stats;
+pub mod gl_config;
+pub mod gl_window;
+pub mod gpu_timer;
+pub mod headless;
+pub mod math;
+pub mod mesh;
+pub mod model;
+pub mod model_viewer;
+pub mod persistence;
+pub mod raster;
+pub mod recorder;
+pub mod redraw;
+pub mod render_target;
+pub mod scene;
+pub mod shader;
+pub mod shader_reload;
+pub mod soft_painter;
+pub mod soft_window;
+pub mod texture;
@@ -1,109 +1,92 @@
-use std::collections::HashMap;
+//! `winit-test`: runs one of the demos, picked by subcommand.
+
 use std::error::Error;
-use std::num::NonZeroU32;
-use std::sync::Arc;
+use std::process::ExitCode;
 
-use softbuffer::{Context, Surface};
-use winit::application::ApplicationHandler;
-use winit::event::WindowEvent;
-use winit::event_loop::{ActiveEventLoop, EventLoop};
-use winit::raw_window_handle::{DisplayHandle, HasDisplayHandle};
-use winit::window::{Window, WindowId};
+use clap::{CommandFactory, Parser, Subcommand};
+use winit_test::cli::CommonArgs;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let event_loop = EventLoop::new()?;
-    let mut app = Application::new(&event_loop)?;
-    event_loop.run_app(&mut app).map_err(Into::into)
-}
+mod demos;
 
-struct Application {
-    context: Context<DisplayHandle<'static>>,
-    windows: HashMap<WindowId, WindowState>,
+#[derive(Debug, Parser)]
+#[command(version, about = "winit + glutin + glow + egui demos")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
 }
 
-struct WindowState {
-    window: Arc<Window>,
-    surface: Surface<DisplayHandle<'static>, Arc<Window>>,
+#[derive(Debug, Subcommand)]
+enum Command {
+    Softbuffer(demos::softbuffer::Args),
+    Glow(demos::glow_run::Args),
+    EguiManual(demos::ai_one::Args),
+    Eframe(demos::eframe_winit::Args),
+    /// List the demos
+    List,
 }
 
-impl Application {
-    fn new(event_loop: &EventLoop<()>) -> Result<Self, Box<dyn Error>> {
-        let context = Context::new(unsafe {
-            std::mem::transmute::<DisplayHandle<'_>, DisplayHandle<'static>>(
-                event_loop.display_handle()?,
-            )
-        })?;
-
-        Ok(Self {
-            context,
-            windows: HashMap::new(),
-        })
+impl Command {
+    /// The options shared by all demos, `None` for `list`.
+    fn common(&self) -> Option<&CommonArgs> {
+        match self {
+            Self::Softbuffer(args) => Some(&args.common),
+            Self::Glow(args) => Some(&args.common),
+            Self::EguiManual(args) => Some(&args.common),
+            Self::Eframe(args) => Some(&args.common),
+            Self::List => None,
+        }
     }
 
-    fn create_window(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Box<dyn Error>> {
-        let window = Arc::new(
-            event_loop.create_window(Window::default_attributes().with_title("Simple Window"))?,
-        );
-
-        let surface = Surface::new(&self.context, Arc::clone(&window))?;
-        let window_id = window.id();
-
-        self.windows
-            .insert(window_id, WindowState { window, surface });
-        Ok(())
+    fn run(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Softbuffer(args) => demos::softbuffer::run(args),
+            Self::Glow(args) => demos::glow_run::run(args),
+            Self::EguiManual(args) => demos::ai_one::run(args),
+            Self::Eframe(args) => demos::eframe_winit::run(args),
+            Self::List => {
+                list_demos();
+                Ok(())
+            }
+        }
     }
 }
 
-impl ApplicationHandler for Application {
-    fn window_event(
-        &mut self,
-        event_loop: &ActiveEventLoop,
-        window_id: WindowId,
-        event: WindowEvent,
-    ) {
-        let window_state = match self.windows.get_mut(&window_id) {
-            Some(window) => window,
-            None => return,
-        };
-
-        match event {
-            WindowEvent::CloseRequested => {
-                self.windows.remove(&window_id);
-                if self.windows.is_empty() {
-                    event_loop.exit();
-                }
-            }
-            WindowEvent::RedrawRequested => {
-                let size = window_state.window.inner_size();
-                if let (Some(width), Some(height)) =
-                    (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
-                {
-                    window_state.surface.resize(width, height).unwrap();
-
-                    let mut buffer = window_state.surface.buffer_mut().unwrap();
-                    // Fill with a simple blue color
-                    for pixel in buffer.iter_mut() {
-                        *pixel = 0xFF0066CC; // ARGB format: blue
-                    }
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match cli.command.common() {
+        Some(common) => common.init_logging(),
+        None => env_logger::init(),
+    }
 
-                    window_state.window.pre_present_notify();
-                    buffer.present().unwrap();
-                }
-            }
-            WindowEvent::Resized(size) => {
-                if let (Some(width), Some(height)) =
-                    (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
-                {
-                    window_state.surface.resize(width, height).unwrap();
-                    window_state.window.request_redraw();
+    match cli.command.run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            // Most errors already quote their source, which isn't repeated
+            let (mut message, mut source) = (err.to_string(), err.source());
+            while let Some(cause) = source {
+                let cause_message = cause.to_string();
+                if !message.contains(&cause_message) {
+                    eprintln!("  caused by: {cause_message}");
                 }
+                (message, source) = (cause_message, cause.source());
             }
-            _ => {}
+            ExitCode::FAILURE
         }
     }
+}
 
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        self.create_window(event_loop)
-            .expect("Failed to create window");
+/// Print every demo subcommand with the first line of its description.
+fn list_demos() {
+    let command = Cli::command();
+    let demos = command
+        .get_subcommands()
+        .filter(|demo| !matches!(demo.get_name(), "list" | "help"));
+    for demo in demos {
+        let about = demo
+            .get_about()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        println!("{:<12} {}", demo.get_name(), about);
     }
 }
@@ -0,0 +1,334 @@
+//! Just enough 3D math for the demos: 3-vectors as plain arrays and a 4x4
+//! matrix laid out the way GL expects it.
+//!
+//! Matrices are column-major, `columns[c][r]`, so they can be handed to
+//! `uniform_matrix_4_f32_slice` without transposing. Projections follow the
+//! GL convention: right-handed view space looking down -z, clip space depth
+//! from -1 to 1.
+
+use std::ops::Mul;
+
+pub type Vec3 = [f32; 3];
+
+pub fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn scale(v: Vec3, s: f32) -> Vec3 {
+    v.map(|x| x * s)
+}
+
+pub fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn length(v: Vec3) -> f32 {
+    dot(v, v).sqrt()
+}
+
+/// `v` scaled to unit length, or zero if it has none.
+pub fn normalize(v: Vec3) -> Vec3 {
+    let length = length(v);
+    if length > 0.0 {
+        scale(v, 1.0 / length)
+    } else {
+        [0.0; 3]
+    }
+}
+
+/// A column-major 4x4 matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub columns: [[f32; 4]; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Self = Self {
+        columns: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub fn translation([x, y, z]: Vec3) -> Self {
+        let mut m = Self::IDENTITY;
+        m.columns[3] = [x, y, z, 1.0];
+        m
+    }
+
+    pub fn scale([x, y, z]: Vec3) -> Self {
+        let mut m = Self::IDENTITY;
+        m.columns[0][0] = x;
+        m.columns[1][1] = y;
+        m.columns[2][2] = z;
+        m
+    }
+
+    /// Counter-clockwise rotation about the x axis, in radians.
+    pub fn rotation_x(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let mut m = Self::IDENTITY;
+        m.columns[1] = [0.0, cos, sin, 0.0];
+        m.columns[2] = [0.0, -sin, cos, 0.0];
+        m
+    }
+
+    /// Counter-clockwise rotation about the y axis, in radians.
+    pub fn rotation_y(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let mut m = Self::IDENTITY;
+        m.columns[0] = [cos, 0.0, -sin, 0.0];
+        m.columns[2] = [sin, 0.0, cos, 0.0];
+        m
+    }
+
+    /// A perspective projection with a vertical field of view in radians.
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        let depth = near - far;
+        Self {
+            columns: [
+                [f / aspect, 0.0, 0.0, 0.0],
+                [0.0, f, 0.0, 0.0],
+                [0.0, 0.0, (far + near) / depth, -1.0],
+                [0.0, 0.0, 2.0 * far * near / depth, 0.0],
+            ],
+        }
+    }
+
+    /// An orthographic projection of the box between the given planes.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let (width, height, depth) = (right - left, top - bottom, far - near);
+        Self {
+            columns: [
+                [2.0 / width, 0.0, 0.0, 0.0],
+                [0.0, 2.0 / height, 0.0, 0.0],
+                [0.0, 0.0, -2.0 / depth, 0.0],
+                [
+                    -(right + left) / width,
+                    -(top + bottom) / height,
+                    -(far + near) / depth,
+                    1.0,
+                ],
+            ],
+        }
+    }
+
+    /// A view matrix for a camera at `eye` looking at `target`.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let forward = normalize(sub(target, eye));
+        let right = normalize(cross(forward, up));
+        let up = cross(right, forward);
+        Self {
+            columns: [
+                [right[0], up[0], -forward[0], 0.0],
+                [right[1], up[1], -forward[1], 0.0],
+                [right[2], up[2], -forward[2], 0.0],
+                [-dot(right, eye), -dot(up, eye), dot(forward, eye), 1.0],
+            ],
+        }
+    }
+
+    /// Transform a point, dropping the w component.
+    pub fn transform_point(&self, [x, y, z]: Vec3) -> Vec3 {
+        let c = &self.columns;
+        std::array::from_fn(|r| c[0][r] * x + c[1][r] * y + c[2][r] * z + c[3][r])
+    }
+
+    /// Transform a point and divide by w, e.g. from world to normalized
+    /// device coordinates through a view-projection matrix.
+    pub fn project_point(&self, [x, y, z]: Vec3) -> Vec3 {
+        let c = &self.columns;
+        let [x, y, z, w]: [f32; 4] =
+            std::array::from_fn(|r| c[0][r] * x + c[1][r] * y + c[2][r] * z + c[3][r]);
+        [x / w, y / w, z / w]
+    }
+
+    /// Transform a direction, ignoring the translation.
+    pub fn transform_vector(&self, [x, y, z]: Vec3) -> Vec3 {
+        let c = &self.columns;
+        std::array::from_fn(|r| c[0][r] * x + c[1][r] * y + c[2][r] * z)
+    }
+
+    /// Transform a surface normal, which has to go through the inverse
+    /// transpose to stay perpendicular under non-uniform scale. The result is
+    /// not normalized.
+    pub fn transform_normal(&self, [x, y, z]: Vec3) -> Vec3 {
+        let [a, b, c] = [0, 1, 2].map(|i| {
+            let [x, y, z, _] = self.columns[i];
+            [x, y, z]
+        });
+        // The cofactors are the inverse transpose scaled by the determinant;
+        // only its sign matters once the result is normalized
+        let [bc, ca, ab] = [cross(b, c), cross(c, a), cross(a, b)];
+        let sign = dot(a, bc).signum();
+        std::array::from_fn(|r| sign * (bc[r] * x + ca[r] * y + ab[r] * z))
+    }
+
+    /// The 16 floats in GL order, for `uniform_matrix_4_f32_slice`.
+    pub fn as_slice(&self) -> &[f32] {
+        self.columns.as_flattened()
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let columns = rhs.columns.map(|column| {
+            std::array::from_fn(|r| (0..4).map(|k| self.columns[k][r] * column[k]).sum())
+        });
+        Self { columns }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_close(actual: Vec3, expected: Vec3) {
+        let close = actual
+            .iter()
+            .zip(expected)
+            .all(|(a, e)| (a - e).abs() < 1e-5);
+        assert!(close, "{actual:?} != {expected:?}");
+    }
+
+    fn assert_mat_close(actual: Mat4, expected: Mat4) {
+        let close = (actual.as_slice().iter())
+            .zip(expected.as_slice())
+            .all(|(a, e)| (a - e).abs() < 1e-5);
+        assert!(close, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn identity_is_neutral() {
+        let m = Mat4::translation([1.0, 2.0, 3.0])
+            * Mat4::rotation_x(0.7)
+            * Mat4::scale([2.0, 3.0, 4.0]);
+        assert_eq!(Mat4::IDENTITY * m, m);
+        assert_eq!(m * Mat4::IDENTITY, m);
+        assert_eq!(
+            Mat4::IDENTITY.transform_point([4.0, 5.0, 6.0]),
+            [4.0, 5.0, 6.0]
+        );
+    }
+
+    #[test]
+    fn mul_applies_the_right_operand_first() {
+        let (t, s) = (Mat4::translation([1.0, 0.0, 0.0]), Mat4::scale([2.0; 3]));
+        assert_close((t * s).transform_point([1.0; 3]), [3.0, 2.0, 2.0]);
+        assert_close((s * t).transform_point([1.0; 3]), [4.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn inverse_transforms_cancel() {
+        let t = [1.0, -2.0, 3.0];
+        assert_mat_close(
+            Mat4::translation(t) * Mat4::translation(scale(t, -1.0)),
+            Mat4::IDENTITY,
+        );
+        assert_mat_close(
+            Mat4::rotation_x(0.4) * Mat4::rotation_x(-0.4),
+            Mat4::IDENTITY,
+        );
+        assert_mat_close(
+            Mat4::rotation_y(1.1) * Mat4::rotation_y(-1.1),
+            Mat4::IDENTITY,
+        );
+        assert_mat_close(
+            Mat4::scale([2.0, 4.0, 0.5]) * Mat4::scale([0.5, 0.25, 2.0]),
+            Mat4::IDENTITY,
+        );
+    }
+
+    #[test]
+    fn rotations_are_counter_clockwise() {
+        assert_close(
+            Mat4::rotation_x(FRAC_PI_2).transform_vector([0.0, 1.0, 0.0]),
+            [0.0, 0.0, 1.0],
+        );
+        assert_close(
+            Mat4::rotation_y(FRAC_PI_2).transform_vector([0.0, 0.0, 1.0]),
+            [1.0, 0.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn perspective_maps_the_frustum_to_ndc() {
+        let (fov_y, aspect, near, far) = (FRAC_PI_2, 2.0, 0.5, 10.0);
+        let m = Mat4::perspective(fov_y, aspect, near, far);
+        assert_close(m.project_point([0.0, 0.0, -near]), [0.0, 0.0, -1.0]);
+        assert_close(m.project_point([0.0, 0.0, -far]), [0.0, 0.0, 1.0]);
+        // A 90 degree field of view reaches as high as it is deep
+        let depth = m.project_point([0.0, 0.0, -4.0])[2];
+        assert_close(m.project_point([0.0, 4.0, -4.0]), [0.0, 1.0, depth]);
+        // and the aspect ratio widens it
+        assert_close(m.project_point([-8.0, -2.0, -4.0]), [-1.0, -0.5, depth]);
+    }
+
+    #[test]
+    fn orthographic_maps_the_box_to_ndc() {
+        let m = Mat4::orthographic(-2.0, 4.0, -1.0, 1.0, 1.0, 3.0);
+        assert_close(m.project_point([-2.0, -1.0, -1.0]), [-1.0, -1.0, -1.0]);
+        assert_close(m.project_point([4.0, 1.0, -3.0]), [1.0, 1.0, 1.0]);
+        assert_close(m.project_point([1.0, 0.0, -2.0]), [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn look_at_moves_the_eye_to_the_origin() {
+        let (eye, target) = ([3.0, 2.0, 5.0], [1.0, 0.0, -1.0]);
+        let view = Mat4::look_at(eye, target, [0.0, 1.0, 0.0]);
+        assert_close(view.transform_point(eye), [0.0; 3]);
+        assert_close(
+            view.transform_point(target),
+            [0.0, 0.0, -length(sub(target, eye))],
+        );
+        // World up stays in the upper half of the view
+        assert!(view.transform_vector([0.0, 1.0, 0.0])[1] > 0.0);
+    }
+
+    #[test]
+    fn normals_stay_perpendicular_under_non_uniform_scale() {
+        let m = Mat4::rotation_y(0.3) * Mat4::scale([3.0, 1.0, 0.5]);
+        let (tangent, normal) = ([1.0, 1.0, 0.0], [1.0, -1.0, 0.0]);
+        let (tangent, normal) = (m.transform_vector(tangent), m.transform_normal(normal));
+        assert!(dot(tangent, normal).abs() < 1e-5);
+        // Transforming the normal like a direction would tilt it
+        assert!(dot(tangent, m.transform_vector([1.0, -1.0, 0.0])).abs() > 1.0);
+    }
+
+    #[test]
+    fn normals_follow_a_mirroring_transform() {
+        let m = Mat4::scale([-1.0, 2.0, 2.0]);
+        assert_close(
+            normalize(m.transform_normal([1.0, 0.0, 0.0])),
+            [-1.0, 0.0, 0.0],
+        );
+        assert_close(
+            normalize(m.transform_normal([0.0, 1.0, 0.0])),
+            [0.0, 1.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn normalize_leaves_zero_alone() {
+        assert_eq!(normalize([0.0; 3]), [0.0; 3]);
+        assert_close(normalize([0.0, 3.0, 4.0]), [0.0, 0.6, 0.8]);
+    }
+}
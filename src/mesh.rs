@@ -0,0 +1,346 @@
+//! Geometry in GL buffers: typed vertex layouts, VBO/IBO upload and indexed
+//! drawing.
+//!
+//! Every vertex attribute has a fixed name and location (see [`Attribute`]),
+//! bound by [`ShaderProgram`](crate::shader::ShaderProgram) before linking.
+//! A vertex shader declares the inputs it uses, e.g. `in vec3 a_position;`,
+//! and can be fed by any mesh whose vertex type provides them; attributes the
+//! mesh has but the shader ignores cost nothing.
+
+use std::mem::{offset_of, size_of, size_of_val};
+use std::sync::Arc;
+
+use glow::HasContext;
+
+/// What a vertex attribute means, which fixes its shader name and location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    Position,
+    Color,
+    Uv,
+    Normal,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 4] = [Self::Position, Self::Color, Self::Uv, Self::Normal];
+
+    /// The attribute location, bound before the program is linked.
+    pub fn location(self) -> u32 {
+        self as u32
+    }
+
+    /// The vertex shader input name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Position => "a_position",
+            Self::Color => "a_color",
+            Self::Uv => "a_uv",
+            Self::Normal => "a_normal",
+        }
+    }
+}
+
+/// Where one attribute lives inside a vertex. All attributes are `f32`s.
+#[derive(Debug, Clone, Copy)]
+pub struct AttributeLayout {
+    pub attribute: Attribute,
+    /// Number of `f32` components, 1 to 4.
+    pub components: i32,
+    /// Byte offset from the start of the vertex.
+    pub offset: usize,
+}
+
+/// A vertex type that can be uploaded as-is.
+///
+/// # Safety
+///
+/// The type must be `#[repr(C)]`, contain only `f32`s and have no padding, so
+/// a slice of it can be viewed as bytes; `LAYOUT` must describe its fields.
+pub unsafe trait Vertex: Copy {
+    const LAYOUT: &'static [AttributeLayout];
+}
+
+/// Position and per-vertex color.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionColor {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+unsafe impl Vertex for PositionColor {
+    const LAYOUT: &'static [AttributeLayout] = &[
+        AttributeLayout {
+            attribute: Attribute::Position,
+            components: 3,
+            offset: offset_of!(Self, position),
+        },
+        AttributeLayout {
+            attribute: Attribute::Color,
+            components: 3,
+            offset: offset_of!(Self, color),
+        },
+    ];
+}
+
+/// Position and texture coordinates.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionUv {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+unsafe impl Vertex for PositionUv {
+    const LAYOUT: &'static [AttributeLayout] = &[
+        AttributeLayout {
+            attribute: Attribute::Position,
+            components: 3,
+            offset: offset_of!(Self, position),
+        },
+        AttributeLayout {
+            attribute: Attribute::Uv,
+            components: 2,
+            offset: offset_of!(Self, uv),
+        },
+    ];
+}
+
+/// Position, normal and texture coordinates, for lit models.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionNormalUv {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+unsafe impl Vertex for PositionNormalUv {
+    const LAYOUT: &'static [AttributeLayout] = &[
+        AttributeLayout {
+            attribute: Attribute::Position,
+            components: 3,
+            offset: offset_of!(Self, position),
+        },
+        AttributeLayout {
+            attribute: Attribute::Normal,
+            components: 3,
+            offset: offset_of!(Self, normal),
+        },
+        AttributeLayout {
+            attribute: Attribute::Uv,
+            components: 2,
+            offset: offset_of!(Self, uv),
+        },
+    ];
+}
+
+/// The demos' triangle in clip space, colored with the gradient `glow_run`
+/// shows (red and green following x and y across the triangle's bounds).
+pub const TRIANGLE: [PositionColor; 3] = [
+    PositionColor {
+        position: [0.0, 0.5, 0.0],
+        color: [0.5, 1.0, 0.5],
+    },
+    PositionColor {
+        position: [-0.5, -0.5, 0.0],
+        color: [0.0, 0.0, 0.5],
+    },
+    PositionColor {
+        position: [0.5, -0.5, 0.0],
+        color: [1.0, 0.0, 0.5],
+    },
+];
+
+/// A square in the middle of clip space with texture coordinates covering
+/// the whole texture, upright (`t = 0` at the top, like image rows).
+pub const QUAD: [PositionUv; 4] = [
+    PositionUv {
+        position: [-0.5, -0.5, 0.0],
+        uv: [0.0, 1.0],
+    },
+    PositionUv {
+        position: [0.5, -0.5, 0.0],
+        uv: [1.0, 1.0],
+    },
+    PositionUv {
+        position: [0.5, 0.5, 0.0],
+        uv: [1.0, 0.0],
+    },
+    PositionUv {
+        position: [-0.5, 0.5, 0.0],
+        uv: [0.0, 0.0],
+    },
+];
+
+/// Two counter-clockwise triangles over [`QUAD`].
+pub const QUAD_INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+/// Indexed triangles in a vertex array object, deleted when dropped.
+pub struct Mesh {
+    gl: Arc<glow::Context>,
+    vertex_array: glow::VertexArray,
+    vertex_buffer: glow::Buffer,
+    index_buffer: glow::Buffer,
+    index_count: i32,
+}
+
+impl Mesh {
+    /// Upload `vertices` and `indices` (triangle list) and record the
+    /// attribute bindings of `V` in a new vertex array object.
+    pub fn new<V: Vertex>(
+        gl: Arc<glow::Context>,
+        vertices: &[V],
+        indices: &[u32],
+    ) -> Result<Self, String> {
+        // SAFETY: `Vertex` guarantees plain `f32` data without padding
+        let vertex_bytes = unsafe {
+            std::slice::from_raw_parts(vertices.as_ptr().cast::<u8>(), size_of_val(vertices))
+        };
+        let index_bytes = unsafe {
+            std::slice::from_raw_parts(indices.as_ptr().cast::<u8>(), size_of_val(indices))
+        };
+
+        unsafe {
+            let vertex_array = gl.create_vertex_array()?;
+            let vertex_buffer = match gl.create_buffer() {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    gl.delete_vertex_array(vertex_array);
+                    return Err(err);
+                }
+            };
+            let index_buffer = match gl.create_buffer() {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    gl.delete_buffer(vertex_buffer);
+                    gl.delete_vertex_array(vertex_array);
+                    return Err(err);
+                }
+            };
+
+            // The element buffer binding is part of the vertex array's state
+            gl.bind_vertex_array(Some(vertex_array));
+
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vertex_buffer));
+            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, vertex_bytes, glow::STATIC_DRAW);
+            for layout in V::LAYOUT {
+                let location = layout.attribute.location();
+                gl.enable_vertex_attrib_array(location);
+                gl.vertex_attrib_pointer_f32(
+                    location,
+                    layout.components,
+                    glow::FLOAT,
+                    false,
+                    size_of::<V>() as i32,
+                    layout.offset as i32,
+                );
+            }
+
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(index_buffer));
+            gl.buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, index_bytes, glow::STATIC_DRAW);
+
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
+
+            Ok(Self {
+                gl,
+                vertex_array,
+                vertex_buffer,
+                index_buffer,
+                index_count: indices.len() as i32,
+            })
+        }
+    }
+
+    /// Draw all triangles with the currently used program.
+    pub fn draw(&self) {
+        unsafe {
+            self.gl.bind_vertex_array(Some(self.vertex_array));
+            self.gl
+                .draw_elements(glow::TRIANGLES, self.index_count, glow::UNSIGNED_INT, 0);
+            self.gl.bind_vertex_array(None);
+        }
+    }
+}
+
+impl Drop for Mesh {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_vertex_array(self.vertex_array);
+            self.gl.delete_buffer(self.vertex_buffer);
+            self.gl.delete_buffer(self.index_buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that `V::LAYOUT` tiles the whole vertex, in order and without
+    /// gaps, and return its `(offset, byte size)` pairs.
+    fn packed_layout<V: Vertex>() -> Vec<(usize, usize)> {
+        let mut end = 0;
+        let fields: Vec<_> = V::LAYOUT
+            .iter()
+            .map(|layout| {
+                assert!((1..=4).contains(&layout.components));
+                assert_eq!(layout.offset, end, "{:?} leaves a gap", layout.attribute);
+                let size = layout.components as usize * size_of::<f32>();
+                end += size;
+                (layout.offset, size)
+            })
+            .collect();
+        assert_eq!(end, size_of::<V>(), "the layout must cover the stride");
+        fields
+    }
+
+    #[test]
+    fn position_color_layout() {
+        assert_eq!(
+            packed_layout::<PositionColor>(),
+            [
+                (offset_of!(PositionColor, position), 12),
+                (offset_of!(PositionColor, color), 12),
+            ]
+        );
+        assert_eq!(size_of::<PositionColor>(), 24);
+    }
+
+    #[test]
+    fn position_uv_layout() {
+        assert_eq!(
+            packed_layout::<PositionUv>(),
+            [
+                (offset_of!(PositionUv, position), 12),
+                (offset_of!(PositionUv, uv), 8),
+            ]
+        );
+        assert_eq!(size_of::<PositionUv>(), 20);
+    }
+
+    #[test]
+    fn position_normal_uv_layout() {
+        assert_eq!(
+            packed_layout::<PositionNormalUv>(),
+            [
+                (offset_of!(PositionNormalUv, position), 12),
+                (offset_of!(PositionNormalUv, normal), 12),
+                (offset_of!(PositionNormalUv, uv), 8),
+            ]
+        );
+        assert_eq!(size_of::<PositionNormalUv>(), 32);
+    }
+
+    #[test]
+    fn attribute_locations_are_distinct() {
+        for (i, a) in Attribute::ALL.iter().enumerate() {
+            for b in &Attribute::ALL[i + 1..] {
+                assert_ne!(a.location(), b.location());
+                assert_ne!(a.name(), b.name());
+            }
+        }
+    }
+}
@@ -0,0 +1,708 @@
+//! Wavefront OBJ and glTF 2.0 models loaded into GPU meshes.
+//!
+//! Loading is split in two: [`ModelData::load`] parses the file and decodes
+//! its textures on the CPU, [`Model::new`] uploads the result. Only what the
+//! viewer draws is kept: positions, normals and one set of texture
+//! coordinates per vertex, and a base color factor and texture per material.
+//! glTF node transforms are baked into the vertices.
+//!
+//! Texture coordinates are stored with `t = 0` at the first row of the image
+//! as decoded, which is how glTF defines them and how images are uploaded.
+//! OBJ puts `v = 0` at the bottom of the image, so its coordinates are flipped
+//! on load.
+
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use glow::HasContext;
+use image::RgbaImage;
+
+use crate::math::{self, Mat4, Vec3};
+use crate::mesh::{Mesh, PositionNormalUv};
+use crate::shader::ShaderProgram;
+use crate::texture::{ColorSpace, Sampling, Texture2D};
+
+/// File formats [`ModelData::load`] understands, picked by extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelFormat {
+    Obj,
+    /// `.gltf` with external or embedded (base64) buffers, or binary `.glb`.
+    Gltf,
+}
+
+impl ModelFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "obj" => Some(Self::Obj),
+            "gltf" | "glb" => Some(Self::Gltf),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `path` has the extension of a format [`ModelData::load`] reads.
+pub fn is_model_file(path: &Path) -> bool {
+    ModelFormat::from_path(path).is_some()
+}
+
+/// A model as read from disk, not yet uploaded.
+#[derive(Debug, Clone, Default)]
+pub struct ModelData {
+    pub meshes: Vec<MeshData>,
+    pub materials: Vec<MaterialData>,
+}
+
+/// Indexed triangles sharing one material.
+#[derive(Debug, Clone)]
+pub struct MeshData {
+    pub vertices: Vec<PositionNormalUv>,
+    pub indices: Vec<u32>,
+    /// Index into [`ModelData::materials`]; `None` uses plain white.
+    pub material: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MaterialData {
+    pub name: String,
+    /// Linear RGBA, multiplied with the texture when there is one.
+    pub base_color: [f32; 4],
+    /// sRGB encoded, as glTF specifies and OBJ files have in practice.
+    pub base_color_texture: Option<RgbaImage>,
+}
+
+impl ModelData {
+    /// Read an `.obj` (with its `.mtl` and textures), `.gltf` or `.glb` file.
+    pub fn load(path: &Path) -> Result<Self, ModelError> {
+        let data = match ModelFormat::from_path(path) {
+            Some(ModelFormat::Obj) => load_obj(path)?,
+            Some(ModelFormat::Gltf) => load_gltf(path)?,
+            None => return Err(ModelError::UnknownFormat(path.to_owned())),
+        };
+        if data.meshes.iter().all(|mesh| mesh.indices.is_empty()) {
+            return Err(ModelError::Empty(path.to_owned()));
+        }
+        Ok(data)
+    }
+}
+
+impl MeshData {
+    /// Fail on the first index past the end of [`MeshData::vertices`], before
+    /// anything indexes with them.
+    fn check_indices(&self, path: &Path) -> Result<(), ModelError> {
+        match self
+            .indices
+            .iter()
+            .find(|&&index| index as usize >= self.vertices.len())
+        {
+            Some(&index) => Err(ModelError::IndexOutOfRange {
+                path: path.to_owned(),
+                index,
+                vertex_count: self.vertices.len(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Smooth vertex normals from the area-weighted normals of the faces
+    /// around each vertex, for files that don't provide any.
+    fn compute_normals(&mut self) {
+        let mut normals = vec![[0.0; 3]; self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize].position);
+            // Not normalized: longer for bigger triangles
+            let face_normal = math::cross(math::sub(b, a), math::sub(c, a));
+            for &index in triangle {
+                let normal = &mut normals[index as usize];
+                *normal = math::add(*normal, face_normal);
+            }
+        }
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            vertex.normal = math::normalize(normal);
+        }
+    }
+}
+
+fn load_obj(path: &Path) -> Result<ModelData, ModelError> {
+    let (models, materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+
+    // A missing .mtl only costs the colors, the geometry is still worth showing
+    let materials = materials.unwrap_or_else(|err| {
+        log::warn!("{}: ignoring materials: {err}", path.display());
+        Vec::new()
+    });
+    let materials: Vec<_> = materials
+        .into_iter()
+        .map(|material| {
+            let [r, g, b] = material.diffuse.unwrap_or([1.0; 3]);
+            let base_color_texture = material.diffuse_texture.and_then(|file| {
+                let image = image::open(dir.join(file)).map(image::DynamicImage::into_rgba8);
+                texture_or_warn(&material.name, image)
+            });
+            MaterialData {
+                name: material.name,
+                base_color: [r, g, b, material.dissolve.unwrap_or(1.0)],
+                base_color_texture,
+            }
+        })
+        .collect();
+
+    let meshes = models
+        .into_iter()
+        .map(|model| {
+            let mesh = model.mesh;
+            let vertices = mesh
+                .positions
+                .chunks_exact(3)
+                .enumerate()
+                .map(|(i, position)| PositionNormalUv {
+                    position: [position[0], position[1], position[2]],
+                    normal: mesh
+                        .normals
+                        .get(i * 3..i * 3 + 3)
+                        .map_or([0.0; 3], |n| [n[0], n[1], n[2]]),
+                    uv: mesh
+                        .texcoords
+                        .get(i * 2..i * 2 + 2)
+                        .map_or([0.0; 2], |uv| [uv[0], 1.0 - uv[1]]),
+                })
+                .collect();
+            let mut data = MeshData {
+                vertices,
+                indices: mesh.indices,
+                material: mesh.material_id.filter(|&id| id < materials.len()),
+            };
+            data.check_indices(path)?;
+            if mesh.normals.is_empty() {
+                data.compute_normals();
+            }
+            Ok(data)
+        })
+        .collect::<Result<_, ModelError>>()?;
+
+    Ok(ModelData { meshes, materials })
+}
+
+fn load_gltf(path: &Path) -> Result<ModelData, ModelError> {
+    let (document, buffers, images) = gltf::import(path)?;
+
+    let materials = document
+        .materials()
+        .map(|material| {
+            let name = material.name().unwrap_or("unnamed").to_owned();
+            let pbr = material.pbr_metallic_roughness();
+            let base_color_texture = pbr.base_color_texture().and_then(|info| {
+                let image = &images[info.texture().source().index()];
+                texture_or_warn(&name, gltf_image(image))
+            });
+            MaterialData {
+                name,
+                base_color: pbr.base_color_factor(),
+                base_color_texture,
+            }
+        })
+        .collect();
+
+    let mut meshes = Vec::new();
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next());
+    for node in scene.iter().flat_map(|scene| scene.nodes()) {
+        collect_gltf_node(path, &node, Mat4::IDENTITY, &buffers, &mut meshes)?;
+    }
+
+    Ok(ModelData { meshes, materials })
+}
+
+/// Append the triangles of `node` and its children, transformed to model space.
+fn collect_gltf_node(
+    path: &Path,
+    node: &gltf::Node,
+    parent: Mat4,
+    buffers: &[gltf::buffer::Data],
+    meshes: &mut Vec<MeshData>,
+) -> Result<(), ModelError> {
+    let transform = parent
+        * Mat4 {
+            columns: node.transform().matrix(),
+        };
+
+    for primitive in node.mesh().iter().flat_map(|mesh| mesh.primitives()) {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            log::warn!(
+                "Skipping {:?} primitive, only triangles are drawn",
+                primitive.mode()
+            );
+            continue;
+        }
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let Some(positions) = reader.read_positions() else {
+            continue;
+        };
+
+        // The texture picks which of the primitive's coordinate sets it uses
+        let material = primitive.material();
+        let uv_set = material
+            .pbr_metallic_roughness()
+            .base_color_texture()
+            .map_or(0, |info| info.tex_coord());
+        let mut normals = reader.read_normals();
+        let mut uvs = reader.read_tex_coords(uv_set).map(|uvs| uvs.into_f32());
+
+        let vertices = positions
+            .map(|position| PositionNormalUv {
+                position: transform.transform_point(position),
+                normal: normals
+                    .as_mut()
+                    .and_then(Iterator::next)
+                    .map_or([0.0; 3], |normal| {
+                        math::normalize(transform.transform_normal(normal))
+                    }),
+                uv: uvs.as_mut().and_then(Iterator::next).unwrap_or([0.0; 2]),
+            })
+            .collect::<Vec<_>>();
+        let indices = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..vertices.len() as u32).collect(),
+        };
+
+        let mut data = MeshData {
+            vertices,
+            indices,
+            material: material.index(),
+        };
+        data.check_indices(path)?;
+        if normals.is_none() {
+            data.compute_normals();
+        }
+        meshes.push(data);
+    }
+
+    for child in node.children() {
+        collect_gltf_node(path, &child, transform, buffers, meshes)?;
+    }
+    Ok(())
+}
+
+/// Convert a decoded glTF image to RGBA8.
+fn gltf_image(image: &gltf::image::Data) -> Result<RgbaImage, String> {
+    use gltf::image::Format;
+
+    let pixels = match image.format {
+        Format::R8G8B8A8 => image.pixels.clone(),
+        Format::R8G8B8 => image
+            .pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], u8::MAX])
+            .collect(),
+        // Grayscale PNGs decode to these
+        Format::R8G8 => image
+            .pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        Format::R8 => image
+            .pixels
+            .iter()
+            .flat_map(|&p| [p, p, p, u8::MAX])
+            .collect(),
+        other => return Err(format!("unsupported pixel format {other:?}")),
+    };
+    RgbaImage::from_raw(image.width, image.height, pixels)
+        .ok_or_else(|| "pixel data does not match image size".to_owned())
+}
+
+/// A failed texture leaves the material untextured rather than failing the model.
+fn texture_or_warn<E: fmt::Display>(
+    material: &str,
+    image: Result<RgbaImage, E>,
+) -> Option<RgbaImage> {
+    image
+        .inspect_err(|err| log::warn!("Material {material}: ignoring base color texture: {err}"))
+        .ok()
+}
+
+/// Axis-aligned bounds of a model, in model space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Bounds {
+    pub fn center(&self) -> Vec3 {
+        math::scale(math::add(self.min, self.max), 0.5)
+    }
+
+    /// Radius of the sphere around [`Bounds::center`] containing the box.
+    pub fn radius(&self) -> f32 {
+        math::length(math::sub(self.max, self.min)) * 0.5
+    }
+}
+
+/// A [`ModelData`] on the GPU.
+pub struct Model {
+    gl: Arc<glow::Context>,
+    primitives: Vec<(Mesh, Option<usize>)>,
+    materials: Vec<Material>,
+    bounds: Bounds,
+}
+
+struct Material {
+    base_color: [f32; 4],
+    texture: Option<Texture2D>,
+}
+
+impl Model {
+    pub fn new(gl: Arc<glow::Context>, data: &ModelData) -> Result<Self, String> {
+        let mut model = Self {
+            gl: gl.clone(),
+            primitives: Vec::with_capacity(data.meshes.len()),
+            materials: Vec::with_capacity(data.materials.len()),
+            bounds: Bounds {
+                min: [f32::INFINITY; 3],
+                max: [f32::NEG_INFINITY; 3],
+            },
+        };
+
+        // Anything created before an error is cleaned up by dropping `model`
+        for material in &data.materials {
+            let texture = match &material.base_color_texture {
+                Some(image) => Some(
+                    Texture2D::new(gl.clone(), image, ColorSpace::Srgb, Sampling::default())
+                        .map_err(|err| err.to_string())?,
+                ),
+                None => None,
+            };
+            model.materials.push(Material {
+                base_color: material.base_color,
+                texture,
+            });
+        }
+
+        for mesh in &data.meshes {
+            for vertex in &mesh.vertices {
+                let bounds = &mut model.bounds;
+                bounds.min = std::array::from_fn(|i| bounds.min[i].min(vertex.position[i]));
+                bounds.max = std::array::from_fn(|i| bounds.max[i].max(vertex.position[i]));
+            }
+            model.primitives.push((
+                Mesh::new(gl.clone(), &mesh.vertices, &mesh.indices)?,
+                mesh.material,
+            ));
+        }
+
+        if model.primitives.is_empty() {
+            model.bounds = Bounds {
+                min: [0.0; 3],
+                max: [0.0; 3],
+            };
+        }
+        Ok(model)
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// Draw every mesh with `program`, which must be in use.
+    ///
+    /// Sets `u_base_color` (`vec4`), `u_has_texture` (`bool`) and
+    /// `u_texture` (`sampler2D`, texture unit 0) per mesh.
+    pub fn draw(&self, program: &ShaderProgram) {
+        let gl = &self.gl;
+        let base_color_location = program.uniform_location("u_base_color");
+        let has_texture_location = program.uniform_location("u_has_texture");
+
+        for (mesh, material) in &self.primitives {
+            let material = material.and_then(|index| self.materials.get(index));
+            let [r, g, b, a] = material.map_or([1.0; 4], |material| material.base_color);
+            let texture = material.and_then(|material| material.texture.as_ref());
+            unsafe {
+                gl.uniform_4_f32(base_color_location.as_ref(), r, g, b, a);
+                gl.uniform_1_i32(has_texture_location.as_ref(), texture.is_some() as i32);
+            }
+            if let Some(texture) = texture {
+                texture.bind(program, "u_texture", 0);
+            }
+            mesh.draw();
+        }
+        unsafe { gl.bind_texture(glow::TEXTURE_2D, None) };
+    }
+}
+
+/// Reasons a model can't be loaded.
+#[derive(Debug)]
+pub enum ModelError {
+    /// The extension is none of `.obj`, `.gltf` or `.glb`.
+    UnknownFormat(PathBuf),
+    Obj(tobj::LoadError),
+    Gltf(gltf::Error),
+    /// The file parsed but contains no triangles.
+    Empty(PathBuf),
+    /// A triangle refers to a vertex the mesh does not have.
+    IndexOutOfRange {
+        path: PathBuf,
+        index: u32,
+        vertex_count: usize,
+    },
+    /// Creating a buffer or texture failed.
+    Gl(String),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFormat(path) => {
+                write!(f, "{}: not an .obj, .gltf or .glb file", path.display())
+            }
+            Self::Obj(err) => write!(f, "failed to load OBJ: {err}"),
+            Self::Gltf(err) => write!(f, "failed to load glTF: {err}"),
+            Self::Empty(path) => write!(f, "{}: no triangles to draw", path.display()),
+            Self::IndexOutOfRange {
+                path,
+                index,
+                vertex_count,
+            } => write!(
+                f,
+                "{}: vertex index {index} out of range for a mesh of {vertex_count} vertices",
+                path.display()
+            ),
+            Self::Gl(err) => write!(f, "failed to upload model: {err}"),
+        }
+    }
+}
+
+impl Error for ModelError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Obj(err) => Some(err),
+            Self::Gltf(err) => Some(err),
+            Self::UnknownFormat(_)
+            | Self::Empty(_)
+            | Self::IndexOutOfRange { .. }
+            | Self::Gl(_) => None,
+        }
+    }
+}
+
+impl From<tobj::LoadError> for ModelError {
+    fn from(err: tobj::LoadError) -> Self {
+        Self::Obj(err)
+    }
+}
+
+impl From<gltf::Error> for ModelError {
+    fn from(err: gltf::Error) -> Self {
+        Self::Gltf(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gltf::image::{Data as GltfImage, Format};
+
+    use super::*;
+
+    fn model_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join("models")
+            .join(name)
+    }
+
+    fn vertex(position: [f32; 3]) -> PositionNormalUv {
+        PositionNormalUv {
+            position,
+            normal: [0.0; 3],
+            uv: [0.0; 2],
+        }
+    }
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{actual:?} != {expected:?}");
+        }
+    }
+
+    fn triangle_count(data: &ModelData) -> usize {
+        data.meshes.iter().map(|mesh| mesh.indices.len() / 3).sum()
+    }
+
+    #[test]
+    fn rejects_out_of_range_indices() {
+        let mesh = MeshData {
+            vertices: vec![vertex([0.0; 3]); 3],
+            indices: vec![0, 1, 2, 2, 1, 3],
+            material: None,
+        };
+        let err = mesh.check_indices(Path::new("bad.obj")).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ModelError::IndexOutOfRange {
+                    index: 3,
+                    vertex_count: 3,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+
+        let mesh = MeshData {
+            indices: vec![0, 1, 2],
+            ..mesh
+        };
+        assert!(mesh.check_indices(Path::new("good.obj")).is_ok());
+    }
+
+    #[test]
+    fn computes_face_normals() {
+        // Counter-clockwise seen from +z
+        let mut mesh = MeshData {
+            vertices: vec![
+                vertex([0.0, 0.0, 0.0]),
+                vertex([1.0, 0.0, 0.0]),
+                vertex([0.0, 1.0, 0.0]),
+            ],
+            indices: vec![0, 1, 2],
+            material: None,
+        };
+        mesh.compute_normals();
+        for vertex in &mesh.vertices {
+            assert_close(vertex.normal, [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn smooths_normals_by_face_area() {
+        // A fold along the x axis: a big face facing +z, a small one facing +y
+        let mut mesh = MeshData {
+            vertices: vec![
+                vertex([0.0, 0.0, 0.0]),
+                vertex([1.0, 0.0, 0.0]),
+                vertex([0.0, 3.0, 0.0]),
+                vertex([0.0, 0.0, -1.0]),
+            ],
+            indices: vec![0, 1, 2, 0, 1, 3],
+            material: None,
+        };
+        mesh.compute_normals();
+        // Shared vertices: the twice-area vectors (0, 0, 3) and (0, 1, 0)
+        let shared = math::normalize([0.0, 1.0, 3.0]);
+        assert_close(mesh.vertices[0].normal, shared);
+        assert_close(mesh.vertices[1].normal, shared);
+        assert_close(mesh.vertices[2].normal, [0.0, 0.0, 1.0]);
+        assert_close(mesh.vertices[3].normal, [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn loads_obj_with_materials() {
+        let data = ModelData::load(&model_path("cube.obj")).unwrap();
+        assert_eq!(triangle_count(&data), 12);
+        let names: Vec<_> = data.materials.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["red", "green", "blue"]);
+        assert_eq!(data.materials[0].base_color, [0.9, 0.2, 0.2, 1.0]);
+        for mesh in &data.meshes {
+            assert!(mesh.material.is_some_and(|id| id < data.materials.len()));
+            assert!(mesh.check_indices(Path::new("cube.obj")).is_ok());
+        }
+    }
+
+    #[test]
+    fn flips_obj_texture_coordinates() {
+        let data = ModelData::load(&model_path("checker_cube.obj")).unwrap();
+        assert!(data.materials[0].base_color_texture.is_some());
+        // `vt 0 0`, `vt 1 0`, `vt 1 1`, `vt 0 1` on the first face
+        let uvs: Vec<_> = data.meshes[0].vertices[..4].iter().map(|v| v.uv).collect();
+        assert_eq!(uvs, [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]]);
+    }
+
+    #[test]
+    fn loads_glb_with_node_transforms() {
+        let data = ModelData::load(&model_path("pyramid.glb")).unwrap();
+        assert_eq!(data.meshes.len(), 1);
+        let mesh = &data.meshes[0];
+        // No index accessor: one triangle per three vertices
+        assert_eq!(mesh.vertices.len(), 18);
+        assert_eq!(mesh.indices, (0..18).collect::<Vec<_>>());
+
+        // The root node scales by 1.5; the apex at y = 1 ends up at 1.5
+        let top = mesh
+            .vertices
+            .iter()
+            .map(|v| v.position[1])
+            .fold(f32::NEG_INFINITY, f32::max);
+        assert!((top - 1.5).abs() < 1e-5, "{top}");
+
+        // Normals were computed, since the file has none
+        for vertex in &mesh.vertices {
+            assert!((math::length(vertex.normal) - 1.0).abs() < 1e-5);
+        }
+
+        let material = &data.materials[mesh.material.unwrap()];
+        assert_eq!(material.name, "checker");
+        assert_eq!(material.base_color, [1.0, 0.9, 0.6, 1.0]);
+        assert!(material.base_color_texture.is_some());
+    }
+
+    #[test]
+    fn embedded_gltf_matches_glb() {
+        let glb = ModelData::load(&model_path("pyramid.glb")).unwrap();
+        let gltf = ModelData::load(&model_path("pyramid_embedded.gltf")).unwrap();
+        assert_eq!(gltf.meshes.len(), glb.meshes.len());
+        for (a, b) in gltf.meshes.iter().zip(&glb.meshes) {
+            assert_eq!(a.vertices, b.vertices);
+            assert_eq!(a.indices, b.indices);
+        }
+        assert_eq!(
+            gltf.materials[0].base_color_texture,
+            glb.materials[0].base_color_texture
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_extensions() {
+        let err = ModelData::load(Path::new("model.stl")).unwrap_err();
+        assert!(matches!(err, ModelError::UnknownFormat(_)), "{err}");
+        assert!(!is_model_file(Path::new("model")));
+        assert!(is_model_file(Path::new("MODEL.GLB")));
+    }
+
+    fn gltf_pixels(format: Format, pixels: &[u8], width: u32) -> Result<Vec<u8>, String> {
+        let image = GltfImage {
+            pixels: pixels.to_vec(),
+            format,
+            width,
+            height: 1,
+        };
+        gltf_image(&image).map(RgbaImage::into_raw)
+    }
+
+    #[test]
+    fn converts_gltf_image_formats() {
+        assert_eq!(
+            gltf_pixels(Format::R8G8B8A8, &[1, 2, 3, 4], 1),
+            Ok(vec![1, 2, 3, 4])
+        );
+        assert_eq!(
+            gltf_pixels(Format::R8G8B8, &[1, 2, 3, 5, 6, 7], 2),
+            Ok(vec![1, 2, 3, 255, 5, 6, 7, 255])
+        );
+        // Gray and gray + alpha
+        assert_eq!(
+            gltf_pixels(Format::R8G8, &[9, 128], 1),
+            Ok(vec![9, 9, 9, 128])
+        );
+        assert_eq!(gltf_pixels(Format::R8, &[9], 1), Ok(vec![9, 9, 9, 255]));
+    }
+
+    #[test]
+    fn rejects_unsupported_or_short_gltf_images() {
+        assert!(gltf_pixels(Format::R16, &[0, 0], 1).is_err());
+        assert!(gltf_pixels(Format::R8G8B8A8, &[1, 2, 3, 4], 2).is_err());
+    }
+}
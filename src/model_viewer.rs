@@ -0,0 +1,207 @@
+//! A file list of the models in `assets/models` and an orbit camera view of
+//! the one picked from it, shared by the demos that can show models.
+//!
+//! Picking "Triangle" unloads the model, and the demo goes back to drawing
+//! its own scene; [`ModelViewer::is_active`] tells which one to draw.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use glow::HasContext;
+
+use crate::assets;
+use crate::camera::OrbitCamera;
+use crate::math::{self, Mat4};
+use crate::model::{self, Model, ModelData, ModelError};
+use crate::shader::ShaderStage;
+use crate::shader_reload::{ReloadableProgram, shader_path};
+
+/// The `assets/models` directory of the [asset root](assets::root), listed
+/// in the "Models" window.
+pub fn model_dir() -> PathBuf {
+    assets::root().join("assets").join("models")
+}
+
+/// The "Models" window and the model it loaded.
+pub struct ModelViewer {
+    gl: Arc<glow::Context>,
+    program: ReloadableProgram,
+    /// The loaded model and the file it came from.
+    model: Option<(PathBuf, Model)>,
+    /// Looks at the model scaled to the unit sphere, by default from above
+    /// and to the side, far enough back to see all of it.
+    pub camera: OrbitCamera,
+
+    /// Whether the "Models" window is shown.
+    pub open: bool,
+    /// Model files in [`model_dir`], listed when the window is first shown.
+    files: Option<Vec<PathBuf>>,
+    /// Why the last pick failed to load.
+    error: Option<String>,
+}
+
+impl ModelViewer {
+    /// Fails when the model shader does not compile for this context.
+    pub fn new(gl: Arc<glow::Context>, glsl_version_header: &str) -> Result<Self, String> {
+        let program = ReloadableProgram::load_required(
+            gl.clone(),
+            glsl_version_header,
+            &[
+                (ShaderStage::Vertex, &shader_path("model.vert")),
+                (ShaderStage::Fragment, &shader_path("model.frag")),
+            ],
+        )?;
+        Ok(Self {
+            gl,
+            program,
+            model: None,
+            camera: OrbitCamera::new(0.6, 0.5, 3.0),
+            open: false,
+            files: None,
+            error: None,
+        })
+    }
+
+    /// Load `path`, replacing the current model. On error the current model
+    /// stays.
+    pub fn load(&mut self, path: &Path) -> Result<(), ModelError> {
+        let data = ModelData::load(path)?;
+        let model = Model::new(self.gl.clone(), &data).map_err(ModelError::Gl)?;
+        log::info!(
+            "Loaded {}: {} meshes, {} materials",
+            path.display(),
+            data.meshes.len(),
+            data.materials.len()
+        );
+        // Canonical, so the file list can tell it apart from the other files
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+        self.model = Some((path, model));
+        Ok(())
+    }
+
+    /// Whether a model is loaded and [`ModelViewer::draw`] should replace the
+    /// demo's own scene.
+    pub fn is_active(&self) -> bool {
+        self.model.is_some()
+    }
+
+    /// Show the "Models" window when open, and the model shader's compile
+    /// error while a model is shown.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if self.is_active() {
+            self.program.show_error_window(ctx);
+        }
+        if !self.open {
+            return;
+        }
+
+        let files = self.files.get_or_insert_with(|| list_models(&model_dir()));
+        let loaded = self.model.as_ref().map(|(path, _)| path);
+        let mut picked = None;
+        let mut unload = false;
+        egui::Window::new("Models")
+            .default_width(220.0)
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                if ui.selectable_label(loaded.is_none(), "Triangle").clicked() {
+                    unload = true;
+                }
+                for path in files.iter() {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    if ui.selectable_label(loaded == Some(path), name).clicked() {
+                        picked = Some(path.clone());
+                    }
+                }
+                if files.is_empty() {
+                    ui.label(format!("No models in {}", model_dir().display()));
+                }
+
+                ui.separator();
+                if ui.button("Refresh").clicked() {
+                    *files = list_models(&model_dir());
+                }
+                if let Some(error) = &self.error {
+                    ui.label(egui::RichText::new(error).color(ui.visuals().error_fg_color));
+                }
+                ui.label("Press M to toggle this window");
+            });
+
+        if unload {
+            self.model = None;
+            self.error = None;
+        }
+        if let Some(path) = picked {
+            self.error = self.load(&path).err().map(|err| {
+                log::error!("Failed to load {}: {err}", path.display());
+                err.to_string()
+            });
+            // The scene was drawn before this pick, show the result right away
+            ctx.request_repaint();
+        }
+    }
+
+    /// Pick up edits to the model shader; on error the last good program
+    /// stays active. Returns `true` when the view needs redrawing.
+    pub fn poll_shaders(&mut self) -> bool {
+        self.program.poll() && self.is_active()
+    }
+
+    /// The model shader's files, for a [`ShaderWatcher`](crate::shader_reload::ShaderWatcher).
+    pub fn shader_paths(&self) -> impl Iterator<Item = &Path> {
+        self.program.paths()
+    }
+
+    /// Draw the model into the current framebuffer as seen by
+    /// [`ModelViewer::camera`]. Clears depth but not color, which is left to
+    /// the caller.
+    pub fn draw(&mut self) {
+        let (Some(program), Some((_, model))) = (self.program.program(), &self.model) else {
+            return;
+        };
+
+        // Scale the model's bounding sphere to the unit sphere
+        let bounds = model.bounds();
+        let radius = bounds.radius().max(f32::EPSILON);
+        let model_matrix =
+            Mat4::scale([1.0 / radius; 3]) * Mat4::translation(math::scale(bounds.center(), -1.0));
+        let view_projection = self.camera.view_projection();
+
+        let gl = &self.gl;
+        program.use_program();
+        unsafe {
+            gl.enable(glow::DEPTH_TEST);
+            gl.clear(glow::DEPTH_BUFFER_BIT);
+            gl.uniform_matrix_4_f32_slice(
+                program.uniform_location("u_model").as_ref(),
+                false,
+                model_matrix.as_slice(),
+            );
+            gl.uniform_matrix_4_f32_slice(
+                program.uniform_location("u_view_projection").as_ref(),
+                false,
+                view_projection.as_slice(),
+            );
+        }
+        model.draw(program);
+        unsafe { gl.disable(glow::DEPTH_TEST) };
+    }
+}
+
+/// The files in `dir` [`ModelData::load`] can read, sorted by name.
+fn list_models(dir: &Path) -> Vec<PathBuf> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_owned());
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::warn!("Failed to list {}: {err}", dir.display());
+            return Vec::new();
+        }
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file() && model::is_model_file(path))
+        .collect();
+    files.sort();
+    files
+}
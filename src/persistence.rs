@@ -0,0 +1,107 @@
+//! UI state that survives restarts, saved as RON in the platform config dir
+//! (`~/.config/winit-test` on Linux).
+//!
+//! The winit demos save through a [`StateFile`] when a window closes and
+//! every [`AUTOSAVE_INTERVAL`] while running. eframe has its own storage, so
+//! `eframe_winit` stores the same [`UiState`] through `eframe::App::save`.
+//! Headless runs never load saved state, so their output stays reproducible.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use directories::ProjectDirs;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// How often running demos save, matching eframe's default.
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The state the demos share: the triangle color and whether the color
+/// picker is open.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub color: [f32; 3],
+    pub show_color_picker: bool,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self {
+            color: [1.0, 0.5, 0.2],
+            show_color_picker: false,
+        }
+    }
+}
+
+/// The crate's directory in the platform config dir, if there is one.
+pub fn config_dir() -> Option<PathBuf> {
+    ProjectDirs::from("", "", env!("CARGO_PKG_NAME")).map(|dirs| dirs.config_dir().to_owned())
+}
+
+/// A RON file in [`config_dir`] holding one demo's saved state.
+///
+/// Failing to read or write it is logged and otherwise ignored: losing the
+/// window layout is no reason to stop a demo.
+#[derive(Debug)]
+pub struct StateFile {
+    path: PathBuf,
+    last_save: Instant,
+}
+
+impl StateFile {
+    /// The file `<name>.ron` in [`config_dir`], or `None` without a config dir.
+    pub fn new(name: &str) -> Option<Self> {
+        let path = config_dir()?.join(format!("{name}.ron"));
+        Some(Self {
+            path,
+            last_save: Instant::now(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The saved state, or `None` if nothing was saved yet or the file can't
+    /// be read.
+    pub fn load<T: DeserializeOwned>(&self) -> Option<T> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                log::warn!("Failed to read {}: {err}", self.path.display());
+                return None;
+            }
+        };
+        ron::from_str(&text)
+            .inspect_err(|err| log::warn!("Ignoring {}: {err}", self.path.display()))
+            .ok()
+    }
+
+    pub fn save<T: Serialize>(&mut self, state: &T) {
+        self.last_save = Instant::now();
+        let text = match ron::ser::to_string_pretty(state, Default::default()) {
+            Ok(text) => text,
+            Err(err) => {
+                log::error!("Failed to serialize UI state: {err}");
+                return;
+            }
+        };
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&self.path, text));
+        match result {
+            Ok(()) => log::debug!("Saved UI state to {}", self.path.display()),
+            Err(err) => log::warn!("Failed to write {}: {err}", self.path.display()),
+        }
+    }
+
+    /// Whether [`AUTOSAVE_INTERVAL`] has passed since the last save.
+    pub fn autosave_due(&self, now: Instant) -> bool {
+        now.duration_since(self.last_save) >= AUTOSAVE_INTERVAL
+    }
+}
@@ -0,0 +1,390 @@
+//! A small CPU triangle rasterizer for the softbuffer demo.
+//!
+//! Triangles are filled with edge functions, sampling at pixel centers and
+//! using the top-left fill rule like GL does, so shared edges are drawn once
+//! and the output lines up with what the GPU renders. Vertex attributes are
+//! interpolated with barycentric weights. Everything is clipped to the target
+//! and to an optional scissor rectangle.
+//!
+//! Pixels are `0x00RRGGBB` `u32`s, the format softbuffer presents.
+
+use image::RgbaImage;
+
+/// A pixel rectangle, `min` inclusive and `max` exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub min: [i32; 2],
+    pub max: [i32; 2],
+}
+
+impl Rect {
+    pub fn from_size(width: u32, height: u32) -> Self {
+        Self {
+            min: [0, 0],
+            max: [width as i32, height as i32],
+        }
+    }
+
+    pub fn intersect(self, other: Rect) -> Rect {
+        Rect {
+            min: [self.min[0].max(other.min[0]), self.min[1].max(other.min[1])],
+            max: [self.max[0].min(other.max[0]), self.max[1].min(other.max[1])],
+        }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.min[0] >= self.max[0] || self.min[1] >= self.max[1]
+    }
+}
+
+/// A mutable view of a pixel buffer.
+pub struct Target<'a> {
+    pub pixels: &'a mut [u32],
+    pub width: u32,
+    pub height: u32,
+}
+
+impl<'a> Target<'a> {
+    pub fn new(pixels: &'a mut [u32], width: u32, height: u32) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize,
+            "pixel buffer does not match target size"
+        );
+        Self {
+            pixels,
+            width,
+            height,
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::from_size(self.width, self.height)
+    }
+
+    pub fn clear(&mut self, color: [f32; 3]) {
+        self.pixels.fill(pack_rgb(color));
+    }
+
+    /// Fill a triangle, interpolating the vertex colors across it.
+    pub fn fill_triangle(&mut self, vertices: &[Vertex; 3], scissor: Option<Rect>) {
+        let clip = scissor.map_or(self.bounds(), |scissor| scissor.intersect(self.bounds()));
+        let positions = vertices.map(|vertex| vertex.position);
+        let width = self.width as usize;
+
+        rasterize(positions, clip, |x, y, weights| {
+            let mut color = [0.0; 3];
+            for (vertex, weight) in vertices.iter().zip(weights) {
+                for (channel, value) in color.iter_mut().zip(vertex.color) {
+                    *channel += value * weight;
+                }
+            }
+            self.pixels[y as usize * width + x as usize] = pack_rgb(color);
+        });
+    }
+}
+
+/// A vertex in pixel coordinates, origin at the top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    pub position: [f32; 2],
+    pub color: [f32; 3],
+}
+
+/// Map a normalized device coordinate (`-1..1`, y up) to pixel coordinates.
+pub fn ndc_to_pixel([x, y]: [f32; 2], width: u32, height: u32) -> [f32; 2] {
+    [
+        (x + 1.0) * 0.5 * width as f32,
+        (1.0 - y) * 0.5 * height as f32,
+    ]
+}
+
+/// Call `shade(x, y, weights)` for every pixel whose center lies inside the
+/// triangle and inside `clip`, with the barycentric weights of that center.
+///
+/// Winding does not matter; degenerate triangles produce no pixels.
+pub fn rasterize(positions: [[f32; 2]; 3], clip: Rect, mut shade: impl FnMut(u32, u32, [f32; 3])) {
+    let [mut a, b, mut c] = positions;
+    let mut area = edge(a, b, c);
+    if area == 0.0 || !area.is_finite() {
+        return;
+    }
+    // Normalize to clockwise (on screen) winding so the fill rule below applies
+    let swapped = area < 0.0;
+    if swapped {
+        std::mem::swap(&mut a, &mut c);
+        area = -area;
+    }
+
+    let bounds = clip.intersect(Rect {
+        min: [
+            a[0].min(b[0]).min(c[0]).floor() as i32,
+            a[1].min(b[1]).min(c[1]).floor() as i32,
+        ],
+        max: [
+            a[0].max(b[0]).max(c[0]).ceil() as i32,
+            a[1].max(b[1]).max(c[1]).ceil() as i32,
+        ],
+    });
+    if bounds.is_empty() {
+        return;
+    }
+
+    // Pixel centers exactly on an edge only belong to the triangle if that is
+    // a top edge (horizontal, going right) or a left edge (going up), so
+    // triangles sharing an edge never both draw it.
+    let top_left =
+        |from: [f32; 2], to: [f32; 2]| (from[1] == to[1] && to[0] > from[0]) || to[1] < from[1];
+    let (top_left_a, top_left_b, top_left_c) = (top_left(b, c), top_left(c, a), top_left(a, b));
+    let inside = |w: f32, top_left: bool| w > 0.0 || (w == 0.0 && top_left);
+
+    for y in bounds.min[1]..bounds.max[1] {
+        for x in bounds.min[0]..bounds.max[0] {
+            let p = [x as f32 + 0.5, y as f32 + 0.5];
+            let w_a = edge(b, c, p);
+            let w_b = edge(c, a, p);
+            let w_c = edge(a, b, p);
+            if !(inside(w_a, top_left_a) && inside(w_b, top_left_b) && inside(w_c, top_left_c)) {
+                continue;
+            }
+
+            let weights = if swapped {
+                // Undo the swap so weights line up with the caller's vertices
+                [w_c / area, w_b / area, w_a / area]
+            } else {
+                [w_a / area, w_b / area, w_c / area]
+            };
+            shade(x as u32, y as u32, weights);
+        }
+    }
+}
+
+/// Twice the signed area of the triangle `a, b, p`; positive when `p` is on
+/// the inner side of edge `a -> b` for clockwise (on screen) triangles.
+fn edge(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// Pack a `0..=1` color into softbuffer's `0x00RRGGBB`.
+pub fn pack_rgb([r, g, b]: [f32; 3]) -> u32 {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0 + 0.5) as u32;
+    (channel(r) << 16) | (channel(g) << 8) | channel(b)
+}
+
+/// Copy a `0x00RRGGBB` buffer into an opaque RGBA image.
+pub fn to_rgba_image(pixels: &[u32], width: u32, height: u32) -> RgbaImage {
+    let bytes = pixels
+        .iter()
+        .flat_map(|&pixel| {
+            let [_, r, g, b] = pixel.to_be_bytes();
+            [r, g, b, 255]
+        })
+        .collect();
+    RgbaImage::from_raw(width, height, bytes).expect("buffer matches image size")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// How often each pixel of a `size` x `size` grid was shaded.
+    fn coverage(triangles: &[[[f32; 2]; 3]], size: u32) -> Vec<u32> {
+        let mut counts = vec![0; (size * size) as usize];
+        for &positions in triangles {
+            rasterize(positions, Rect::from_size(size, size), |x, y, _| {
+                counts[(y * size + x) as usize] += 1;
+            });
+        }
+        counts
+    }
+
+    /// Every pixel shaded with its weights, in scan order.
+    fn shaded(positions: [[f32; 2]; 3], clip: Rect) -> Vec<(u32, u32, [f32; 3])> {
+        let mut pixels = Vec::new();
+        rasterize(positions, clip, |x, y, weights| {
+            pixels.push((x, y, weights))
+        });
+        pixels
+    }
+
+    fn assert_weights_eq(actual: [f32; 3], expected: [f32; 3]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn shared_diagonal_is_drawn_once() {
+        // The diagonal runs through the centers of the pixels on it
+        let (top_left, top_right, bottom_left, bottom_right) =
+            ([0.0, 0.0], [8.0, 0.0], [0.0, 8.0], [8.0, 8.0]);
+        let counts = coverage(
+            &[
+                [top_left, top_right, bottom_right],
+                [top_left, bottom_right, bottom_left],
+            ],
+            8,
+        );
+        assert!(counts.iter().all(|&count| count == 1), "{counts:?}");
+    }
+
+    #[test]
+    fn shared_edges_through_pixel_centers_are_drawn_once() {
+        // A fan around the center, every edge on a row or column of centers
+        let center = [4.5, 4.5];
+        let corners = [[0.5, 0.5], [8.5, 0.5], [8.5, 8.5], [0.5, 8.5]];
+        let triangles: Vec<_> = (0..4)
+            .map(|i| [center, corners[i], corners[(i + 1) % 4]])
+            .collect();
+        let counts = coverage(&triangles, 9);
+        // Only the top and left edges of the square belong to it
+        for y in 0..9 {
+            for x in 0..9 {
+                let expected = u32::from(x < 8 && y < 8);
+                assert_eq!(counts[y * 9 + x], expected, "pixel {x}, {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn weights_are_one_at_each_vertex() {
+        let corner = [0.5, 0.5];
+        let others = [[8.5, 0.5], [0.5, 8.5]];
+        let orders = [
+            [corner, others[0], others[1]],
+            [others[1], corner, others[0]],
+            [others[0], others[1], corner],
+        ];
+        for (slot, positions) in orders.into_iter().enumerate() {
+            let pixels = shaded(positions, Rect::from_size(16, 16));
+            let &(_, _, weights) = pixels
+                .iter()
+                .find(|&&(x, y, _)| (x, y) == (0, 0))
+                .expect("top-left vertex is covered");
+            let mut expected = [0.0; 3];
+            expected[slot] = 1.0;
+            assert_weights_eq(weights, expected);
+        }
+    }
+
+    #[test]
+    fn weights_sum_to_one() {
+        for (_, _, weights) in shaded(
+            [[1.0, 2.0], [13.0, 5.0], [4.0, 15.0]],
+            Rect::from_size(16, 16),
+        ) {
+            assert!(
+                (weights.iter().sum::<f32>() - 1.0).abs() < 1e-5,
+                "{weights:?}"
+            );
+            assert!(
+                weights.iter().all(|&w| (0.0..=1.0).contains(&w)),
+                "{weights:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn winding_does_not_matter() {
+        let [a, b, c] = [[1.0, 2.0], [13.0, 5.0], [4.0, 15.0]];
+        let clip = Rect::from_size(16, 16);
+        let clockwise = shaded([a, b, c], clip);
+        let counter_clockwise = shaded([c, b, a], clip);
+        assert!(!clockwise.is_empty());
+        assert_eq!(clockwise.len(), counter_clockwise.len());
+        for (&(x, y, [w_a, w_b, w_c]), &(x2, y2, reversed)) in
+            clockwise.iter().zip(&counter_clockwise)
+        {
+            assert_eq!((x, y), (x2, y2));
+            // Weights follow the vertices, not their position in the list
+            assert_weights_eq(reversed, [w_c, w_b, w_a]);
+        }
+    }
+
+    #[test]
+    fn degenerate_triangles_draw_nothing() {
+        let clip = Rect::from_size(8, 8);
+        assert!(shaded([[0.0, 0.0], [4.0, 4.0], [8.0, 8.0]], clip).is_empty());
+        assert!(shaded([[2.0, 2.0]; 3], clip).is_empty());
+        assert!(shaded([[0.0, 0.0], [f32::NAN, 4.0], [8.0, 0.0]], clip).is_empty());
+    }
+
+    #[test]
+    fn clips_to_the_target() {
+        let mut pixels = vec![0; 4 * 3];
+        let mut target = Target::new(&mut pixels, 4, 3);
+        let white = Vertex {
+            position: [0.0; 2],
+            color: [1.0; 3],
+        };
+        // Far bigger than the target on every side
+        let triangle = [[-100.0, -100.0], [100.0, -100.0], [0.0, 100.0]]
+            .map(|position| Vertex { position, ..white });
+        target.fill_triangle(&triangle, None);
+        assert!(pixels.iter().all(|&pixel| pixel == 0xFF_FF_FF));
+
+        // Entirely off screen
+        let mut pixels = vec![0; 4 * 3];
+        let mut target = Target::new(&mut pixels, 4, 3);
+        let triangle = [[-10.0, -10.0], [-1.0, -10.0], [-1.0, -1.0]]
+            .map(|position| Vertex { position, ..white });
+        target.fill_triangle(&triangle, None);
+        assert!(pixels.iter().all(|&pixel| pixel == 0));
+    }
+
+    #[test]
+    fn scissor_limits_the_fill() {
+        let (width, height) = (8, 6);
+        let mut pixels = vec![0; width * height];
+        let mut target = Target::new(&mut pixels, width as u32, height as u32);
+        let triangle = [[-50.0, -50.0], [50.0, -50.0], [0.0, 50.0]].map(|position| Vertex {
+            position,
+            color: [1.0, 0.0, 0.0],
+        });
+        // Reaches past the right edge, so it is also clipped to the target
+        let scissor = Rect {
+            min: [2, 1],
+            max: [20, 4],
+        };
+        target.fill_triangle(&triangle, Some(scissor));
+        for y in 0..height {
+            for x in 0..width {
+                let expected = if x >= 2 && (1..4).contains(&y) {
+                    0xFF_00_00
+                } else {
+                    0
+                };
+                assert_eq!(pixels[y * width + x], expected, "pixel {x}, {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn empty_scissor_draws_nothing() {
+        let mut pixels = vec![0; 16];
+        let mut target = Target::new(&mut pixels, 4, 4);
+        let triangle = [[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]].map(|position| Vertex {
+            position,
+            color: [1.0; 3],
+        });
+        let scissor = Rect {
+            min: [2, 2],
+            max: [2, 4],
+        };
+        target.fill_triangle(&triangle, Some(scissor));
+        assert!(pixels.iter().all(|&pixel| pixel == 0));
+    }
+
+    #[test]
+    fn maps_ndc_corners_to_pixel_corners() {
+        assert_eq!(ndc_to_pixel([-1.0, 1.0], 800, 600), [0.0, 0.0]);
+        assert_eq!(ndc_to_pixel([1.0, -1.0], 800, 600), [800.0, 600.0]);
+        assert_eq!(ndc_to_pixel([0.0, 0.0], 800, 600), [400.0, 300.0]);
+    }
+
+    #[test]
+    fn packs_and_clamps_colors() {
+        assert_eq!(pack_rgb([1.0, 0.5, 0.0]), 0xFF_80_00);
+        assert_eq!(pack_rgb([2.0, -1.0, 1.0]), 0xFF_00_FF);
+    }
+}
@@ -0,0 +1,521 @@
+//! Recording every rendered frame to a numbered PNG sequence or a Y4M video.
+//!
+//! Frames are read back asynchronously: `read_pixels` copies into one of a
+//! ring of pixel buffer objects and returns immediately, and the buffer is
+//! only mapped a few frames later once its fence has signalled. Encoding and
+//! writing happen on a separate thread, so the render loop only pays for a
+//! memcpy per frame.
+//!
+//! The recorder also provides the simulated clock to drive the app with, so a
+//! recording advances by exactly one timestep per frame no matter how long
+//! the frame took to render.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use glow::HasContext;
+use image::RgbaImage;
+
+/// Read-backs that can be in flight before the oldest one has to be waited on.
+const BUFFERS_IN_FLIGHT: usize = 3;
+
+/// Frames read back but not yet written, before handing over more blocks.
+const WRITE_QUEUE: usize = 8;
+
+/// `GL_SIGNALED`, which glow doesn't export.
+const SIGNALED: i32 = 0x9119;
+
+/// Where and how fast to record.
+#[derive(Debug, Clone)]
+pub struct RecordOptions {
+    /// A directory for numbered PNGs, or a `.y4m` file.
+    pub output: PathBuf,
+    /// Simulated frames per second.
+    pub fps: u32,
+}
+
+impl RecordOptions {
+    pub fn new(output: PathBuf) -> Self {
+        Self { output, fps: 60 }
+    }
+
+    /// The simulated time between two frames.
+    pub fn timestep(&self) -> Duration {
+        Duration::from_secs(1) / self.fps
+    }
+
+    fn format(&self) -> RecordFormat {
+        if self
+            .output
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("y4m"))
+        {
+            RecordFormat::Y4m
+        } else {
+            RecordFormat::Png
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecordFormat {
+    /// `frame_000000.png`, `frame_000001.png`, … in the output directory.
+    Png,
+    /// An uncompressed YUV4MPEG2 stream (4:4:4), which ffmpeg and most
+    /// players read directly.
+    Y4m,
+}
+
+/// A pixel buffer object being read into.
+struct PendingReadback {
+    buffer: glow::Buffer,
+    /// Allocated size of `buffer` in bytes.
+    capacity: usize,
+    fence: glow::Fence,
+    size: [u32; 2],
+}
+
+/// Records frames read back from the current GL framebuffer to disk.
+pub struct FrameRecorder {
+    timestep: Duration,
+    /// Frames captured so far, which is also the simulated clock.
+    frames: u64,
+
+    /// Pixel buffers not currently in use, with their allocated sizes in bytes.
+    free: Vec<(glow::Buffer, usize)>,
+    pending: VecDeque<PendingReadback>,
+
+    /// `None` once the writer has been shut down.
+    sender: Option<SyncSender<RgbaImage>>,
+    writer: Option<JoinHandle<Result<u64, RecordError>>>,
+    /// The error that stopped the recording early, reported by `finish`.
+    error: Option<RecordError>,
+}
+
+impl FrameRecorder {
+    /// Prepare the output and start the writer thread.
+    ///
+    /// PNG output creates the directory if it doesn't exist yet.
+    pub fn new(options: &RecordOptions) -> Result<Self, RecordError> {
+        let sink = match options.format() {
+            RecordFormat::Png => {
+                fs::create_dir_all(&options.output)?;
+                Sink::Png {
+                    dir: options.output.clone(),
+                }
+            }
+            RecordFormat::Y4m => Sink::Y4m {
+                file: BufWriter::new(File::create(&options.output)?),
+                fps: options.fps,
+                size: None,
+            },
+        };
+
+        let (sender, receiver) = sync_channel(WRITE_QUEUE);
+        let writer = thread::Builder::new()
+            .name("frame writer".into())
+            .spawn(move || sink.run(receiver))?;
+
+        log::info!(
+            "Recording at {} fps to {}",
+            options.fps,
+            options.output.display()
+        );
+        Ok(Self {
+            timestep: options.timestep(),
+            frames: 0,
+            free: Vec::new(),
+            pending: VecDeque::new(),
+            sender: Some(sender),
+            writer: Some(writer),
+            error: None,
+        })
+    }
+
+    /// Simulated time of the next frame, in seconds since recording started.
+    pub fn time(&self) -> f64 {
+        self.frames as f64 * self.timestep.as_secs_f64()
+    }
+
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    /// Whether the writer is still accepting frames.
+    pub fn is_recording(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Start an asynchronous read-back of the currently bound framebuffer.
+    ///
+    /// Call after the frame is drawn and before buffers are swapped. Frames
+    /// whose read-back has completed are handed to the writer on the way.
+    pub fn capture(&mut self, gl: &glow::Context, [width, height]: [u32; 2]) {
+        if !self.is_recording() {
+            return;
+        }
+        self.collect(gl, false);
+        if self.pending.len() >= BUFFERS_IN_FLIGHT {
+            // Every frame has to end up in the recording, so wait for the
+            // oldest read-back rather than dropping this one
+            self.collect(gl, true);
+        }
+
+        let (buffer, capacity) = match self.free.pop() {
+            Some(free) => free,
+            None => match unsafe { gl.create_buffer() } {
+                Ok(buffer) => (buffer, 0),
+                Err(err) => return self.fail(RecordError::Gl(err)),
+            },
+        };
+
+        let len = width as usize * height as usize * 4;
+        unsafe {
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(buffer));
+            if capacity < len {
+                gl.buffer_data_size(glow::PIXEL_PACK_BUFFER, len as i32, glow::STREAM_READ);
+            }
+            gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+            gl.read_pixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::BufferOffset(0),
+            );
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+        }
+        let capacity = capacity.max(len);
+
+        match unsafe { gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0) } {
+            Ok(fence) => {
+                self.pending.push_back(PendingReadback {
+                    buffer,
+                    capacity,
+                    fence,
+                    size: [width, height],
+                });
+                self.frames += 1;
+            }
+            Err(err) => {
+                self.free.push((buffer, capacity));
+                self.fail(RecordError::Gl(err));
+            }
+        }
+    }
+
+    /// Hand finished read-backs to the writer, oldest first. With `block`,
+    /// waits for the oldest one even if it hasn't finished yet.
+    fn collect(&mut self, gl: &glow::Context, mut block: bool) {
+        while let Some(readback) = self.pending.front() {
+            let signaled =
+                unsafe { gl.get_sync_parameter_i32(readback.fence, glow::SYNC_STATUS) == SIGNALED };
+            // Mapping a buffer waits for the GPU on its own
+            if !signaled && !block {
+                break;
+            }
+            block = false;
+
+            let readback = self.pending.pop_front().expect("checked above");
+            let image = unsafe { map_readback(gl, &readback) };
+            unsafe { gl.delete_sync(readback.fence) };
+            self.free.push((readback.buffer, readback.capacity));
+
+            self.send(image);
+        }
+    }
+
+    fn send(&mut self, image: RgbaImage) -> bool {
+        let Some(sender) = &self.sender else {
+            return false;
+        };
+        if sender.send(image).is_ok() {
+            return true;
+        }
+        // The writer only hangs up after an error; collect it
+        if let Err(err) = self.finish_writer() {
+            self.fail(err);
+        }
+        false
+    }
+
+    /// Stop recording after `err`, keeping what was written so far.
+    fn fail(&mut self, err: RecordError) {
+        log::error!("Recording stopped: {err}");
+        self.sender = None;
+        self.error.get_or_insert(err);
+    }
+
+    /// Wait for all outstanding read-backs, write them and close the output.
+    ///
+    /// Returns the number of frames written. Needs the same GL context
+    /// current as [`FrameRecorder::capture`] did.
+    pub fn finish(&mut self, gl: &glow::Context) -> Result<u64, RecordError> {
+        while !self.pending.is_empty() {
+            self.collect(gl, true);
+        }
+        for (buffer, _) in self.free.drain(..) {
+            unsafe { gl.delete_buffer(buffer) };
+        }
+        let written = self.finish_writer();
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => written,
+        }
+    }
+
+    fn finish_writer(&mut self) -> Result<u64, RecordError> {
+        self.sender = None;
+        match self.writer.take() {
+            Some(writer) => writer.join().unwrap_or(Err(RecordError::WriterPanicked)),
+            None => Ok(0),
+        }
+    }
+}
+
+/// Copy a finished read-back out of its pixel buffer, flipped to top-down rows.
+unsafe fn map_readback(gl: &glow::Context, readback: &PendingReadback) -> RgbaImage {
+    let [width, height] = readback.size;
+    let row_len = width as usize * 4;
+    let len = row_len * height as usize;
+    let mut pixels = vec![0_u8; len];
+
+    unsafe {
+        gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(readback.buffer));
+        let mapped =
+            gl.map_buffer_range(glow::PIXEL_PACK_BUFFER, 0, len as i32, glow::MAP_READ_BIT);
+        if mapped.is_null() {
+            log::error!("Failed to map pixel buffer, recording a blank frame");
+        } else {
+            let mapped = std::slice::from_raw_parts(mapped, len);
+            for (dst, src) in pixels
+                .chunks_exact_mut(row_len)
+                .zip(mapped.chunks_exact(row_len).rev())
+            {
+                dst.copy_from_slice(src);
+            }
+            gl.unmap_buffer(glow::PIXEL_PACK_BUFFER);
+        }
+        gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+    }
+
+    RgbaImage::from_raw(width, height, pixels).expect("buffer matches image size")
+}
+
+/// The writer thread's end of the recording.
+enum Sink {
+    Png {
+        dir: PathBuf,
+    },
+    Y4m {
+        file: BufWriter<File>,
+        fps: u32,
+        /// Set by the first frame; Y4M can't change size mid-stream.
+        size: Option<[u32; 2]>,
+    },
+}
+
+impl Sink {
+    fn run(mut self, receiver: Receiver<RgbaImage>) -> Result<u64, RecordError> {
+        let mut frames = 0;
+        for mut image in receiver {
+            // The window's alpha channel means nothing to a video
+            for pixel in image.pixels_mut() {
+                pixel[3] = u8::MAX;
+            }
+            self.write(frames, &image)?;
+            frames += 1;
+        }
+        if let Self::Y4m { file, .. } = &mut self {
+            file.flush()?;
+        }
+        Ok(frames)
+    }
+
+    fn write(&mut self, index: u64, image: &RgbaImage) -> Result<(), RecordError> {
+        match self {
+            Self::Png { dir } => image.save(frame_path(dir, index))?,
+            Self::Y4m { file, fps, size } => {
+                let frame_size = [image.width(), image.height()];
+                match size {
+                    None => {
+                        write_y4m_header(file, frame_size, *fps)?;
+                        *size = Some(frame_size);
+                    }
+                    Some(size) if *size != frame_size => {
+                        return Err(RecordError::SizeChanged {
+                            from: *size,
+                            to: frame_size,
+                        });
+                    }
+                    Some(_) => {}
+                }
+                writeln!(file, "FRAME")?;
+                write_yuv444(file, image)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn frame_path(dir: &Path, index: u64) -> PathBuf {
+    dir.join(format!("frame_{index:06}.png"))
+}
+
+/// The stream header: progressive, square pixels, 4:4:4 chroma.
+fn write_y4m_header(out: &mut impl Write, [width, height]: [u32; 2], fps: u32) -> io::Result<()> {
+    writeln!(out, "YUV4MPEG2 W{width} H{height} F{fps}:1 Ip A1:1 C444")
+}
+
+/// Write `image` as planar Y, Cb, Cr with BT.601 limited-range coefficients,
+/// the default ffmpeg assumes for Y4M.
+fn write_yuv444(out: &mut impl Write, image: &RgbaImage) -> io::Result<()> {
+    let convert = |weights: [f32; 3], offset: f32| {
+        image
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b, _] = pixel.0.map(f32::from);
+                (offset + weights[0] * r + weights[1] * g + weights[2] * b).round() as u8
+            })
+            .collect::<Vec<_>>()
+    };
+    out.write_all(&convert([0.257, 0.504, 0.098], 16.0))?;
+    out.write_all(&convert([-0.148, -0.291, 0.439], 128.0))?;
+    out.write_all(&convert([0.439, -0.368, -0.071], 128.0))
+}
+
+#[derive(Debug)]
+pub enum RecordError {
+    Io(io::Error),
+    Image(image::ImageError),
+    /// Creating a pixel buffer or fence failed.
+    Gl(String),
+    /// The window was resized while recording to a format with a fixed size.
+    SizeChanged {
+        from: [u32; 2],
+        to: [u32; 2],
+    },
+    WriterPanicked,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to write recording: {err}"),
+            Self::Image(err) => write!(f, "failed to write frame: {err}"),
+            Self::Gl(err) => write!(f, "failed to read back frame: {err}"),
+            Self::SizeChanged { from, to } => write!(
+                f,
+                "frame size changed from {}x{} to {}x{}, which Y4M cannot represent",
+                from[0], from[1], to[0], to[1]
+            ),
+            Self::WriterPanicked => write!(f, "the frame writer thread panicked"),
+        }
+    }
+}
+
+impl Error for RecordError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Image(err) => Some(err),
+            Self::Gl(_) | Self::SizeChanged { .. } | Self::WriterPanicked => None,
+        }
+    }
+}
+
+impl From<io::Error> for RecordError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<image::ImageError> for RecordError {
+    fn from(err: image::ImageError) -> Self {
+        Self::Image(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    fn yuv444(pixels: &[[u8; 4]]) -> Vec<u8> {
+        let image = RgbaImage::from_fn(pixels.len() as u32, 1, |x, _| Rgba(pixels[x as usize]));
+        let mut out = Vec::new();
+        write_yuv444(&mut out, &image).unwrap();
+        out
+    }
+
+    #[test]
+    fn converts_to_limited_range_bt601() {
+        // Planar: all Y, then all Cb, then all Cr
+        assert_eq!(
+            yuv444(&[[0, 0, 0, 255], [255, 255, 255, 255]]),
+            [16, 235, 128, 128, 128, 128]
+        );
+        assert_eq!(yuv444(&[[255, 0, 0, 255]]), [82, 90, 240]);
+        assert_eq!(yuv444(&[[0, 255, 0, 255]]), [145, 54, 34]);
+        assert_eq!(yuv444(&[[0, 0, 255, 255]]), [41, 240, 110]);
+    }
+
+    #[test]
+    fn ignores_alpha() {
+        assert_eq!(yuv444(&[[255, 0, 0, 0]]), yuv444(&[[255, 0, 0, 255]]));
+    }
+
+    #[test]
+    fn empty_image_writes_nothing() {
+        assert!(yuv444(&[]).is_empty());
+    }
+
+    #[test]
+    fn writes_y4m_header() {
+        let mut out = Vec::new();
+        write_y4m_header(&mut out, [640, 480], 30).unwrap();
+        assert_eq!(out, b"YUV4MPEG2 W640 H480 F30:1 Ip A1:1 C444\n");
+    }
+
+    #[test]
+    fn picks_format_by_extension() {
+        let format = |output: &str| RecordOptions::new(output.into()).format();
+        assert_eq!(format("out.y4m"), RecordFormat::Y4m);
+        assert_eq!(format("out.Y4M"), RecordFormat::Y4m);
+        assert_eq!(format("frames"), RecordFormat::Png);
+        assert_eq!(format("out.y4m.d/"), RecordFormat::Png);
+    }
+
+    #[test]
+    fn y4m_rejects_a_size_change() {
+        let path = std::env::temp_dir().join(format!("recorder-test-{}.y4m", std::process::id()));
+        let mut sink = Sink::Y4m {
+            file: BufWriter::new(File::create(&path).unwrap()),
+            fps: 60,
+            size: None,
+        };
+        let result = sink
+            .write(0, &RgbaImage::new(2, 2))
+            .and_then(|()| sink.write(1, &RgbaImage::new(2, 2)))
+            .and_then(|()| sink.write(2, &RgbaImage::new(3, 2)));
+        drop(sink);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            result,
+            Err(RecordError::SizeChanged {
+                from: [2, 2],
+                to: [3, 2]
+            })
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+//! Event-driven redraw scheduling.
+//!
+//! Instead of redrawing every window on every loop iteration, a window is
+//! redrawn when an input event asks for it or when egui's `repaint_delay`
+//! runs out, and the event loop sleeps with [`ControlFlow::WaitUntil`] in
+//! between. egui keeps the delay at zero while an animation is running, so the
+//! loop only spins continuously for as long as something on screen is moving.
+//!
+//! Continuous mode redraws unconditionally, for benchmarking.
+
+use std::time::{Duration, Instant};
+
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
+use winit::window::Window;
+
+/// Tracks when a single window needs its next frame.
+#[derive(Debug, Default)]
+pub struct RedrawScheduler {
+    /// Redraw on every loop iteration regardless of what egui asks for.
+    pub continuous: bool,
+    next_redraw: Option<Instant>,
+}
+
+impl RedrawScheduler {
+    pub fn new(continuous: bool) -> Self {
+        Self {
+            continuous,
+            next_redraw: None,
+        }
+    }
+
+    /// Call after drawing a frame with the delay the frame asked for.
+    ///
+    /// Replaces any earlier deadline, since the frame just drawn satisfied it.
+    /// [`Duration::MAX`], egui's "no repaint needed", clears the deadline.
+    pub fn frame_drawn(&mut self, repaint_delay: Duration) {
+        self.next_redraw = Instant::now().checked_add(repaint_delay);
+    }
+
+    /// Request a redraw of `window` if one is due, and return when the event
+    /// loop should wake up next for this window.
+    pub fn poll(&mut self, window: &Window, now: Instant) -> Option<Instant> {
+        let (redraw, next_wake) = self.due(now);
+        if redraw {
+            window.request_redraw();
+        }
+        next_wake
+    }
+
+    /// Whether a redraw is due at `now`, clearing the deadline when it is, and
+    /// the deadline still ahead otherwise.
+    fn due(&mut self, now: Instant) -> (bool, Option<Instant>) {
+        if self.continuous {
+            return (true, None);
+        }
+
+        match self.next_redraw {
+            Some(deadline) if deadline <= now => {
+                self.next_redraw = None;
+                (true, None)
+            }
+            deadline => (false, deadline),
+        }
+    }
+}
+
+/// Sleep until the earliest of `deadlines`, or until the next event if there is none.
+pub fn set_control_flow(event_loop: &ActiveEventLoop, deadlines: impl Iterator<Item = Instant>) {
+    event_loop.set_control_flow(match deadlines.min() {
+        Some(deadline) => ControlFlow::WaitUntil(deadline),
+        None => ControlFlow::Wait,
+    });
+}
+
+/// How long until egui wants the root viewport repainted.
+pub fn repaint_delay(full_output: &egui::FullOutput) -> Duration {
+    full_output
+        .viewport_output
+        .get(&egui::ViewportId::ROOT)
+        .map_or(Duration::MAX, |output| output.repaint_delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LATER: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn nothing_is_due_before_the_first_frame() {
+        let mut scheduler = RedrawScheduler::new(false);
+        assert_eq!(scheduler.due(Instant::now()), (false, None));
+    }
+
+    #[test]
+    fn zero_delay_is_due_once() {
+        let mut scheduler = RedrawScheduler::new(false);
+        scheduler.frame_drawn(Duration::ZERO);
+        let now = Instant::now();
+        assert_eq!(scheduler.due(now), (true, None));
+        assert_eq!(scheduler.due(now), (false, None));
+    }
+
+    #[test]
+    fn waits_for_a_future_deadline() {
+        let mut scheduler = RedrawScheduler::new(false);
+        let before = Instant::now();
+        scheduler.frame_drawn(LATER);
+        let (redraw, deadline) = scheduler.due(Instant::now());
+        assert!(!redraw);
+        let deadline = deadline.unwrap();
+        assert!(deadline >= before + LATER);
+        // Still pending until it passes, then due exactly once
+        assert_eq!(scheduler.due(Instant::now()), (false, Some(deadline)));
+        assert_eq!(scheduler.due(deadline), (true, None));
+    }
+
+    #[test]
+    fn a_new_frame_replaces_the_deadline() {
+        let mut scheduler = RedrawScheduler::new(false);
+        scheduler.frame_drawn(Duration::ZERO);
+        scheduler.frame_drawn(LATER);
+        assert!(!scheduler.due(Instant::now()).0);
+    }
+
+    #[test]
+    fn max_delay_never_redraws() {
+        let mut scheduler = RedrawScheduler::new(false);
+        scheduler.frame_drawn(Duration::MAX);
+        assert_eq!(scheduler.due(Instant::now() + LATER), (false, None));
+    }
+
+    #[test]
+    fn continuous_always_redraws() {
+        let mut scheduler = RedrawScheduler::new(true);
+        scheduler.frame_drawn(Duration::MAX);
+        let now = Instant::now();
+        assert_eq!(scheduler.due(now), (true, None));
+        assert_eq!(scheduler.due(now), (true, None));
+    }
+}
@@ -0,0 +1,169 @@
+//! Offscreen rendering into a texture, for showing a GL scene inside egui.
+//!
+//! A [`RenderTarget`] is a framebuffer object with a color texture and a
+//! depth/stencil renderbuffer. The texture is registered with the egui_glow
+//! painter as a native texture and then drawn like any other image, so the
+//! scene can live in a panel or window of its own, at its own resolution.
+
+use std::sync::Arc;
+
+use glow::HasContext;
+
+/// A framebuffer object rendering into a texture.
+///
+/// The color texture is meant to be handed to egui with
+/// `egui_glow::Painter::register_native_texture` (or eframe's
+/// `Frame::register_native_glow_texture`), which takes ownership and deletes
+/// it together with the painter. Dropping a `RenderTarget` therefore only
+/// deletes the framebuffer and its depth buffer. Resizing reallocates the
+/// texture's storage in place, so the registered `TextureId` stays valid.
+pub struct RenderTarget {
+    gl: Arc<glow::Context>,
+    framebuffer: glow::Framebuffer,
+    texture: glow::Texture,
+    depth_stencil: glow::Renderbuffer,
+    size: [u32; 2],
+}
+
+impl RenderTarget {
+    /// Create a target of `size` pixels, clamped to at least 1x1.
+    pub fn new(gl: Arc<glow::Context>, size: [u32; 2]) -> Result<Self, String> {
+        let (framebuffer, texture, depth_stencil) = unsafe {
+            let texture = gl.create_texture()?;
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            for (parameter, value) in [
+                (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+                (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+                (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+                (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+            ] {
+                gl.tex_parameter_i32(glow::TEXTURE_2D, parameter, value as i32);
+            }
+            gl.bind_texture(glow::TEXTURE_2D, None);
+
+            let depth_stencil = gl.create_renderbuffer().inspect_err(|_| {
+                gl.delete_texture(texture);
+            })?;
+            let framebuffer = gl.create_framebuffer().inspect_err(|_| {
+                gl.delete_renderbuffer(depth_stencil);
+                gl.delete_texture(texture);
+            })?;
+            (framebuffer, texture, depth_stencil)
+        };
+
+        let mut target = Self {
+            gl,
+            framebuffer,
+            texture,
+            depth_stencil,
+            size: [0, 0],
+        };
+        if let Err(err) = target.allocate(size) {
+            // Not handed to egui yet, so still ours to delete
+            unsafe { target.gl.delete_texture(target.texture) };
+            return Err(err);
+        }
+        Ok(target)
+    }
+
+    /// The color texture, to register with egui.
+    pub fn texture(&self) -> glow::Texture {
+        self.texture
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    /// Change the resolution, keeping the same texture object. A no-op when
+    /// the size is unchanged.
+    pub fn resize(&mut self, size: [u32; 2]) -> Result<(), String> {
+        if size.map(|side| side.max(1)) == self.size {
+            return Ok(());
+        }
+        self.allocate(size)
+    }
+
+    fn allocate(&mut self, size: [u32; 2]) -> Result<(), String> {
+        let gl = &self.gl;
+        let [width, height] = size.map(|side| side.max(1) as i32);
+
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA8 as i32,
+                width,
+                height,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(None),
+            );
+            gl.bind_texture(glow::TEXTURE_2D, None);
+
+            gl.bind_renderbuffer(glow::RENDERBUFFER, Some(self.depth_stencil));
+            gl.renderbuffer_storage(glow::RENDERBUFFER, glow::DEPTH24_STENCIL8, width, height);
+            gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+
+            let previous = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(self.texture),
+                0,
+            );
+            gl.framebuffer_renderbuffer(
+                glow::FRAMEBUFFER,
+                glow::DEPTH_STENCIL_ATTACHMENT,
+                glow::RENDERBUFFER,
+                Some(self.depth_stencil),
+            );
+            let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, previous);
+
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(format!("framebuffer incomplete: {status:#x}"));
+            }
+        }
+
+        self.size = [width as u32, height as u32];
+        Ok(())
+    }
+
+    /// Run `draw` with this target bound and the viewport covering it, then
+    /// restore the previous framebuffer and viewport.
+    ///
+    /// Restoring matters because the caller may itself be rendering into a
+    /// framebuffer object (e.g. headless mode), not the default framebuffer.
+    pub fn draw<R>(&self, draw: impl FnOnce(&glow::Context) -> R) -> R {
+        let gl = &self.gl;
+        let [width, height] = self.size;
+        unsafe {
+            let previous = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+            let mut viewport = [0; 4];
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut viewport);
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            gl.viewport(0, 0, width as i32, height as i32);
+            let result = draw(gl);
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, previous);
+            let [x, y, width, height] = viewport;
+            gl.viewport(x, y, width, height);
+            result
+        }
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_framebuffer(self.framebuffer);
+            self.gl.delete_renderbuffer(self.depth_stencil);
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! Scenes a demo can switch between at runtime, and the gallery panel that
+//! lists them.
+//!
+//! A [`Scene`] is cheap to construct and owns no GL objects until
+//! [`Scene::init`]; [`Scene::destroy`] frees them again. The [`Gallery`] keeps
+//! only the scene on screen initialized, so switching deletes the old scene's
+//! buffers, programs and textures before the new one creates its own.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use glow::HasContext;
+
+use crate::camera::OrbitCamera;
+use crate::mesh::{self, Mesh};
+use crate::shader::ShaderStage;
+use crate::shader_reload::{ReloadableProgram, shader_path};
+use crate::texture::{self, FilterPreset, Sampling, Texture2D, TextureError, Wrap};
+
+/// What a scene draws a frame with.
+pub struct Frame<'a> {
+    pub gl: &'a glow::Context,
+    /// The color picker's color, for scenes that have a single color.
+    pub color: [f32; 3],
+    pub camera: &'a OrbitCamera,
+}
+
+/// Something the gallery can show: a name, GL resources created on demand
+/// and the code to draw them.
+pub trait Scene {
+    /// Identifies the scene on the command line, e.g. `textured-quad`.
+    fn id(&self) -> &'static str;
+
+    /// The label in the gallery.
+    fn name(&self) -> &'static str;
+
+    /// Create the scene's GL resources. Called again after [`Scene::destroy`]
+    /// when the scene is picked a second time.
+    fn init(
+        &mut self,
+        gl: &Arc<glow::Context>,
+        glsl_version_header: &str,
+    ) -> Result<(), SceneError>;
+
+    /// Per-frame work besides drawing, such as showing shader errors.
+    fn update(&mut self, _ctx: &egui::Context) {}
+
+    /// Recompile shaders whose files changed, returning `true` when the scene
+    /// needs redrawing. Called between frames, not for every frame.
+    fn poll_shaders(&mut self) -> bool {
+        false
+    }
+
+    /// The scene's options, shown in the gallery under the scene list.
+    fn settings_ui(&mut self, _ui: &mut egui::Ui) {}
+
+    /// Draw into the current framebuffer, which the caller has cleared.
+    fn render(&mut self, frame: &Frame);
+
+    /// Free everything [`Scene::init`] created.
+    fn destroy(&mut self);
+}
+
+/// The scenes every gallery starts with, none of them initialized.
+pub fn default_scenes() -> Vec<Box<dyn Scene>> {
+    vec![
+        Box::new(TriangleScene::colored()),
+        Box::new(TriangleScene::gradient()),
+        Box::new(TexturedQuad::default()),
+    ]
+}
+
+/// The ids of [`default_scenes`], for validating command line arguments.
+pub fn default_scene_ids() -> Vec<&'static str> {
+    default_scenes().iter().map(|scene| scene.id()).collect()
+}
+
+/// The "Scenes" side panel and the scene it has on screen.
+pub struct Gallery {
+    gl: Arc<glow::Context>,
+    glsl_version_header: String,
+    scenes: Vec<Box<dyn Scene>>,
+    /// Index of the initialized scene in `scenes`, `None` when none could be.
+    current: Option<usize>,
+
+    /// Whether the side panel is shown.
+    pub open: bool,
+    /// Why the last pick failed to initialize.
+    error: Option<String>,
+}
+
+impl Gallery {
+    /// A gallery of [`default_scenes`], showing the first.
+    pub fn new(gl: Arc<glow::Context>, glsl_version_header: &str) -> Result<Self, SceneError> {
+        Self::with_scenes(gl, glsl_version_header, default_scenes())
+    }
+
+    /// A gallery of `scenes`, showing the first.
+    pub fn with_scenes(
+        gl: Arc<glow::Context>,
+        glsl_version_header: &str,
+        scenes: Vec<Box<dyn Scene>>,
+    ) -> Result<Self, SceneError> {
+        let mut gallery = Self {
+            gl,
+            glsl_version_header: glsl_version_header.to_owned(),
+            scenes,
+            current: None,
+            open: false,
+            error: None,
+        };
+        if !gallery.scenes.is_empty() {
+            gallery.select(0)?;
+        }
+        Ok(gallery)
+    }
+
+    /// The scene on screen.
+    pub fn current(&self) -> Option<&dyn Scene> {
+        Some(self.scenes[self.current?].as_ref())
+    }
+
+    /// Switch to the scene with `id`.
+    pub fn select_id(&mut self, id: &str) -> Result<(), SceneError> {
+        let index = self
+            .scenes
+            .iter()
+            .position(|scene| scene.id() == id)
+            .ok_or_else(|| SceneError::Unknown(id.to_owned()))?;
+        self.select(index)
+    }
+
+    /// Destroy the current scene and initialize `scenes[index]`. If that
+    /// fails, the previous scene is brought back.
+    fn select(&mut self, index: usize) -> Result<(), SceneError> {
+        let previous = self.current.take();
+        if let Some(previous) = previous {
+            self.scenes[previous].destroy();
+        }
+
+        let result = self.init(index);
+        match &result {
+            Ok(()) => {
+                log::info!("Showing scene {}", self.scenes[index].name());
+                self.current = Some(index);
+            }
+            Err(err) => {
+                let cause = err.source().map(|source| format!(": {source}"));
+                log::error!(
+                    "Failed to set up {}: {err}{}",
+                    self.scenes[index].name(),
+                    cause.unwrap_or_default()
+                );
+                self.scenes[index].destroy();
+                if let Some(previous) = previous
+                    && self.init(previous).is_ok()
+                {
+                    self.current = Some(previous);
+                }
+            }
+        }
+        result
+    }
+
+    fn init(&mut self, index: usize) -> Result<(), SceneError> {
+        self.scenes[index].init(&self.gl, &self.glsl_version_header)
+    }
+
+    /// Run the current scene's [`Scene::poll_shaders`].
+    pub fn poll_shaders(&mut self) -> bool {
+        self.current
+            .is_some_and(|current| self.scenes[current].poll_shaders())
+    }
+
+    /// Run the current scene's [`Scene::update`].
+    pub fn update(&mut self, ctx: &egui::Context) {
+        if let Some(current) = self.current {
+            self.scenes[current].update(ctx);
+        }
+    }
+
+    /// Show the side panel when open.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut picked = None;
+        egui::SidePanel::left("gallery")
+            .default_width(180.0)
+            .show(ctx, |ui| {
+                ui.heading("Scenes");
+                ui.separator();
+                for (index, scene) in self.scenes.iter().enumerate() {
+                    if ui
+                        .selectable_label(self.current == Some(index), scene.name())
+                        .clicked()
+                        && self.current != Some(index)
+                    {
+                        picked = Some(index);
+                    }
+                }
+                if let Some(error) = &self.error {
+                    ui.label(egui::RichText::new(error).color(ui.visuals().error_fg_color));
+                }
+
+                if let Some(current) = self.current {
+                    ui.separator();
+                    self.scenes[current].settings_ui(ui);
+                }
+
+                ui.separator();
+                ui.label("Press G to toggle this panel");
+            });
+
+        if let Some(index) = picked {
+            self.error = self.select(index).err().map(|err| err.to_string());
+            ctx.request_repaint();
+        }
+    }
+
+    /// Draw the current scene.
+    pub fn render(&mut self, color: [f32; 3], camera: &OrbitCamera) {
+        if let Some(current) = self.current {
+            self.scenes[current].render(&Frame {
+                gl: &self.gl,
+                color,
+                camera,
+            });
+        }
+    }
+}
+
+impl Drop for Gallery {
+    fn drop(&mut self) {
+        if let Some(current) = self.current.take() {
+            self.scenes[current].destroy();
+        }
+    }
+}
+
+/// The triangle drawn with one program from `shaders/`: a single color from
+/// the color picker (`u_color`) or the per-vertex gradient `glow_run` draws.
+pub struct TriangleScene {
+    id: &'static str,
+    name: &'static str,
+    /// Vertex and fragment shader file names.
+    shaders: [&'static str; 2],
+    resources: Option<(ReloadableProgram, Mesh)>,
+}
+
+impl TriangleScene {
+    /// The triangle in the color picker's color.
+    pub fn colored() -> Self {
+        Self::new("triangle", "Triangle", ["triangle.vert", "triangle.frag"])
+    }
+
+    /// The triangle with its vertex colors blended across it.
+    pub fn gradient() -> Self {
+        Self::new(
+            "gradient-triangle",
+            "Gradient triangle",
+            ["gradient.vert", "gradient.frag"],
+        )
+    }
+
+    fn new(id: &'static str, name: &'static str, shaders: [&'static str; 2]) -> Self {
+        Self {
+            id,
+            name,
+            shaders,
+            resources: None,
+        }
+    }
+}
+
+impl Scene for TriangleScene {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn init(
+        &mut self,
+        gl: &Arc<glow::Context>,
+        glsl_version_header: &str,
+    ) -> Result<(), SceneError> {
+        let triangle =
+            Mesh::new(gl.clone(), &mesh::TRIANGLE, &[0, 1, 2]).map_err(SceneError::Gl)?;
+        let [vertex, fragment] = self.shaders;
+        let program = ReloadableProgram::load_required(
+            gl.clone(),
+            glsl_version_header,
+            &[
+                (ShaderStage::Vertex, &shader_path(vertex)),
+                (ShaderStage::Fragment, &shader_path(fragment)),
+            ],
+        )
+        .map_err(SceneError::Shader)?;
+        self.resources = Some((program, triangle));
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &egui::Context) {
+        if let Some((program, _)) = &self.resources {
+            program.show_error_window(ctx);
+        }
+    }
+
+    fn poll_shaders(&mut self) -> bool {
+        self.resources
+            .as_mut()
+            .is_some_and(|(program, _)| program.poll())
+    }
+
+    fn render(&mut self, frame: &Frame) {
+        let Some((program, triangle)) = &self.resources else {
+            return;
+        };
+        let Some(program) = program.program() else {
+            return;
+        };
+
+        program.use_program();
+        // The gradient shader has no `u_color`, which makes setting it a no-op
+        let [r, g, b] = frame.color;
+        unsafe {
+            frame
+                .gl
+                .uniform_3_f32(program.uniform_location("u_color").as_ref(), r, g, b);
+            frame.gl.uniform_matrix_4_f32_slice(
+                program.uniform_location("u_view_projection").as_ref(),
+                false,
+                frame.camera.view_projection().as_slice(),
+            );
+        }
+        triangle.draw();
+    }
+
+    fn destroy(&mut self) {
+        self.resources = None;
+    }
+}
+
+/// A quad showing `assets/textures/uv_grid.jpg`, with its filtering and
+/// wrapping picked in the gallery.
+pub struct TexturedQuad {
+    resources: Option<TexturedQuadResources>,
+    /// Kept over a destroy and init.
+    filter: FilterPreset,
+    wrap: Wrap,
+}
+
+struct TexturedQuadResources {
+    program: ReloadableProgram,
+    quad: Mesh,
+    texture: Texture2D,
+}
+
+impl TexturedQuad {
+    const TEXTURE: &str = "uv_grid.jpg";
+
+    fn sampling(&self) -> Sampling {
+        Sampling::preset(self.filter, self.wrap)
+    }
+}
+
+impl Default for TexturedQuad {
+    /// Trilinear filtering, repeating.
+    fn default() -> Self {
+        Self {
+            resources: None,
+            filter: FilterPreset::Trilinear,
+            wrap: Wrap::Repeat,
+        }
+    }
+}
+
+impl Scene for TexturedQuad {
+    fn id(&self) -> &'static str {
+        "textured-quad"
+    }
+
+    fn name(&self) -> &'static str {
+        "Textured quad"
+    }
+
+    fn init(
+        &mut self,
+        gl: &Arc<glow::Context>,
+        glsl_version_header: &str,
+    ) -> Result<(), SceneError> {
+        let quad =
+            Mesh::new(gl.clone(), &mesh::QUAD, &mesh::QUAD_INDICES).map_err(SceneError::Gl)?;
+        let texture = Texture2D::load(
+            gl.clone(),
+            &texture::texture_path(Self::TEXTURE),
+            texture::ColorSpace::Srgb,
+            self.sampling(),
+        )?;
+        let program = ReloadableProgram::load_required(
+            gl.clone(),
+            glsl_version_header,
+            &[
+                (ShaderStage::Vertex, &shader_path("textured.vert")),
+                (ShaderStage::Fragment, &shader_path("textured.frag")),
+            ],
+        )
+        .map_err(SceneError::Shader)?;
+        self.resources = Some(TexturedQuadResources {
+            program,
+            quad,
+            texture,
+        });
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &egui::Context) {
+        if let Some(resources) = &self.resources {
+            resources.program.show_error_window(ctx);
+        }
+    }
+
+    fn poll_shaders(&mut self) -> bool {
+        self.resources
+            .as_mut()
+            .is_some_and(|resources| resources.program.poll())
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        let previous = self.sampling();
+        ui.label("Filter:");
+        for preset in FilterPreset::ALL {
+            ui.radio_value(&mut self.filter, preset, preset.name());
+        }
+        ui.label("Wrap:");
+        for wrap in Wrap::ALL {
+            ui.radio_value(&mut self.wrap, wrap, format!("{wrap:?}"));
+        }
+
+        let sampling = self.sampling();
+        if sampling != previous
+            && let Some(resources) = &mut self.resources
+        {
+            resources.texture.set_sampling(sampling);
+            ui.ctx().request_repaint();
+        }
+    }
+
+    fn render(&mut self, frame: &Frame) {
+        let Some(resources) = &mut self.resources else {
+            return;
+        };
+        let Some(program) = resources.program.program() else {
+            return;
+        };
+
+        program.use_program();
+        unsafe {
+            frame.gl.uniform_matrix_4_f32_slice(
+                program.uniform_location("u_view_projection").as_ref(),
+                false,
+                frame.camera.view_projection().as_slice(),
+            );
+        }
+        resources.texture.bind(program, "u_texture", 0);
+        resources.quad.draw();
+    }
+
+    fn destroy(&mut self) {
+        self.resources = None;
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    /// No scene has the requested id.
+    Unknown(String),
+    /// The driver refused to create a buffer or vertex array.
+    Gl(String),
+    /// A texture could not be loaded.
+    Texture(TextureError),
+    /// A shader did not compile for this context.
+    Shader(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(id) => write!(f, "unknown scene: {id}"),
+            Self::Gl(err) => write!(f, "failed to create scene geometry: {err}"),
+            Self::Texture(_) => write!(f, "failed to load scene texture"),
+            Self::Shader(err) => write!(f, "failed to build scene shader: {err}"),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Texture(err) => Some(err),
+            Self::Unknown(_) | Self::Gl(_) | Self::Shader(_) => None,
+        }
+    }
+}
+
+impl From<TextureError> for SceneError {
+    fn from(err: TextureError) -> Self {
+        Self::Texture(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_error_is_reported_once_in_a_chain() {
+        let err = SceneError::from(TextureError::Gl("out of memory".to_owned()));
+        assert_eq!(err.to_string(), "failed to load scene texture");
+        let source = err.source().expect("the texture error is the source");
+        assert_eq!(
+            source.to_string(),
+            "failed to create texture: out of memory"
+        );
+    }
+}
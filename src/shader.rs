@@ -0,0 +1,365 @@
+//! Shader program compilation with recoverable errors.
+//!
+//! GLSL sources are written without a `#version` line; the header is passed in
+//! separately and prepended when compiling, so the same source can be reused
+//! across GL versions. Compile errors report line numbers relative to the
+//! source as written, not to the header-prefixed string the driver saw.
+//!
+//! Vertex inputs named after a [`mesh::Attribute`] get that attribute's
+//! location, so any [`Mesh`](crate::mesh::Mesh) can feed any program.
+//!
+//! Sources use GLSL 1.30 / GLSL ES 3.00 `in`/`out` variables. For a
+//! `#version 100` header (OpenGL ES 2) each stage gets a prelude mapping them
+//! onto `attribute`, `varying` and `gl_FragColor`; see [`legacy_source`].
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use glow::HasContext;
+
+use crate::mesh;
+
+/// Pipeline stage a shader source is compiled for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+impl ShaderStage {
+    fn gl_type(self) -> u32 {
+        match self {
+            Self::Vertex => glow::VERTEX_SHADER,
+            Self::Fragment => glow::FRAGMENT_SHADER,
+        }
+    }
+
+    /// Defines mapping GLSL ES 3.00 names onto their GLSL ES 1.00 equivalents.
+    fn legacy_prelude(self) -> &'static str {
+        match self {
+            Self::Vertex => {
+                "#define in attribute\n#define out varying\n#define texture texture2D\n"
+            }
+            Self::Fragment => "#define in varying\n#define texture texture2D\n",
+        }
+    }
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Vertex => f.write_str("vertex"),
+            Self::Fragment => f.write_str("fragment"),
+        }
+    }
+}
+
+/// A linked GL program, deleted when dropped.
+pub struct ShaderProgram {
+    gl: Arc<glow::Context>,
+    program: glow::Program,
+}
+
+impl ShaderProgram {
+    /// Compile and link a program from `sources`, prepending `version_header`
+    /// (e.g. `"#version 410"`) to every stage.
+    pub fn new(
+        gl: Arc<glow::Context>,
+        version_header: &str,
+        sources: &[(ShaderStage, &str)],
+    ) -> Result<Self, ShaderError> {
+        let legacy = version_header.trim() == "#version 100";
+
+        unsafe {
+            let program = gl.create_program().map_err(ShaderError::Create)?;
+            let mut shaders = Vec::with_capacity(sources.len());
+
+            let compiled = sources.iter().try_for_each(|&(stage, source)| {
+                let shader = gl
+                    .create_shader(stage.gl_type())
+                    .map_err(ShaderError::Create)?;
+                gl.attach_shader(program, shader);
+                shaders.push(shader);
+
+                let (prelude, body) = if legacy {
+                    (stage.legacy_prelude(), legacy_source(stage, source))
+                } else {
+                    ("", Cow::Borrowed(source))
+                };
+                gl.shader_source(shader, &format!("{version_header}\n{prelude}{body}"));
+                gl.compile_shader(shader);
+                if !gl.get_shader_compile_status(shader) {
+                    let info_log = gl.get_shader_info_log(shader);
+                    let header_lines = version_header.lines().count() + prelude.lines().count();
+                    return Err(ShaderError::Compile(CompileError::new(
+                        stage,
+                        info_log,
+                        source,
+                        header_lines,
+                    )));
+                }
+                Ok(())
+            });
+
+            let linked = compiled.and_then(|()| {
+                for attribute in mesh::Attribute::ALL {
+                    gl.bind_attrib_location(program, attribute.location(), attribute.name());
+                }
+                gl.link_program(program);
+                if gl.get_program_link_status(program) {
+                    Ok(())
+                } else {
+                    Err(ShaderError::Link(gl.get_program_info_log(program)))
+                }
+            });
+
+            for shader in shaders {
+                gl.detach_shader(program, shader);
+                gl.delete_shader(shader);
+            }
+
+            match linked {
+                Ok(()) => Ok(Self { gl, program }),
+                Err(err) => {
+                    gl.delete_program(program);
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    pub fn raw(&self) -> glow::Program {
+        self.program
+    }
+
+    pub fn use_program(&self) {
+        unsafe { self.gl.use_program(Some(self.program)) };
+    }
+
+    pub fn uniform_location(&self, name: &str) -> Option<glow::UniformLocation> {
+        unsafe { self.gl.get_uniform_location(self.program, name) }
+    }
+}
+
+/// `source` with its fragment output declaration, e.g. `out vec4 color;`,
+/// replaced by a define aliasing the name to `gl_FragColor`, which GLSL ES 1.00
+/// has instead of user-declared outputs. Keeps the line count, so compile
+/// errors still point at the right line.
+fn legacy_source(stage: ShaderStage, source: &str) -> Cow<'_, str> {
+    if stage != ShaderStage::Fragment {
+        return Cow::Borrowed(source);
+    }
+    let output = |line: &str| match line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["out", "vec4", name] => name.strip_suffix(';').map(str::to_owned),
+        _ => None,
+    };
+    Cow::Owned(
+        source
+            .lines()
+            .map(|line| match output(line) {
+                Some(name) => format!("#define {name} gl_FragColor\n"),
+                None => format!("{line}\n"),
+            })
+            .collect(),
+    )
+}
+
+impl Drop for ShaderProgram {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_program(self.program) };
+    }
+}
+
+/// Reasons [`ShaderProgram::new`] can fail.
+#[derive(Debug)]
+pub enum ShaderError {
+    /// The driver refused to create a shader or program object.
+    Create(String),
+    /// A stage failed to compile.
+    Compile(CompileError),
+    /// All stages compiled but linking them failed; holds the program info log.
+    Link(String),
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Create(err) => write!(f, "failed to create GL shader object: {err}"),
+            Self::Compile(err) => err.fmt(f),
+            Self::Link(log) => write!(f, "failed to link shader program:\n{}", log.trim_end()),
+        }
+    }
+}
+
+impl Error for ShaderError {}
+
+/// A failed compile of a single stage.
+#[derive(Debug, Clone)]
+pub struct CompileError {
+    pub stage: ShaderStage,
+    /// The raw driver info log.
+    pub info_log: String,
+    /// Info log entries the driver attached a line number to.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// One info log message mapped back onto the original source.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// 1-based line in the source as passed to [`ShaderProgram::new`].
+    pub line: usize,
+    pub message: String,
+    /// The offending source line, when the line number is in range.
+    pub source_line: Option<String>,
+}
+
+impl CompileError {
+    fn new(stage: ShaderStage, info_log: String, source: &str, header_lines: usize) -> Self {
+        let diagnostics = info_log
+            .lines()
+            .filter_map(parse_log_line)
+            .filter_map(|(gl_line, message)| {
+                let line = gl_line.checked_sub(header_lines).filter(|&l| l > 0)?;
+                Some(Diagnostic {
+                    line,
+                    message: message.to_owned(),
+                    source_line: source.lines().nth(line - 1).map(|l| l.trim().to_owned()),
+                })
+            })
+            .collect();
+
+        Self {
+            stage,
+            info_log,
+            diagnostics,
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to compile {} shader", self.stage)?;
+        if self.diagnostics.is_empty() {
+            return write!(f, ":\n{}", self.info_log.trim_end());
+        }
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n  line {}: {}", diagnostic.line, diagnostic.message)?;
+            if let Some(source_line) = &diagnostic.source_line {
+                write!(f, "\n    | {source_line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extract the line number and message from one info log line.
+///
+/// Drivers disagree on the format, the common ones are:
+/// Mesa `0:12(5): error: ...`, Apple/ANGLE `ERROR: 0:12: ...` and
+/// NVIDIA `0(12) : error C0000: ...`.
+fn parse_log_line(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim();
+    let rest = line
+        .strip_prefix("ERROR: ")
+        .or_else(|| line.strip_prefix("WARNING: "))
+        .unwrap_or(line);
+
+    let is_file_id = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    if let Some((file, tail)) = rest.split_once(':')
+        && is_file_id(file)
+    {
+        let digits = tail.bytes().take_while(u8::is_ascii_digit).count();
+        let number = tail[..digits].parse().ok()?;
+        let message = tail[digits..]
+            .split_once(':')
+            .map_or(&tail[digits..], |(_, message)| message);
+        return Some((number, message.trim()));
+    }
+
+    if let Some((file, tail)) = rest.split_once('(')
+        && is_file_id(file)
+    {
+        let (number, message) = tail.split_once(')')?;
+        let message = message.trim_start().trim_start_matches(':');
+        return Some((number.parse().ok()?, message.trim()));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mesa_log_line() {
+        assert_eq!(
+            parse_log_line("0:12(5): error: `color' undeclared"),
+            Some((12, "error: `color' undeclared"))
+        );
+    }
+
+    #[test]
+    fn parses_angle_log_line() {
+        assert_eq!(
+            parse_log_line("ERROR: 0:3: 'vec5' : no matching overloaded function found"),
+            Some((3, "'vec5' : no matching overloaded function found"))
+        );
+        assert_eq!(
+            parse_log_line("WARNING: 0:7: extension not supported"),
+            Some((7, "extension not supported"))
+        );
+    }
+
+    #[test]
+    fn parses_nvidia_log_line() {
+        assert_eq!(
+            parse_log_line("0(12) : error C0000: syntax error, unexpected '}'"),
+            Some((12, "error C0000: syntax error, unexpected '}'"))
+        );
+    }
+
+    #[test]
+    fn trims_surrounding_whitespace() {
+        assert_eq!(
+            parse_log_line("  0:1(1): error: x  \r"),
+            Some((1, "error: x"))
+        );
+    }
+
+    #[test]
+    fn legacy_source_aliases_the_fragment_output() {
+        let source =
+            "precision mediump float;\nout vec4 color;\nvoid main() {\n    color = vec4(1.0);\n}";
+        let legacy = legacy_source(ShaderStage::Fragment, source);
+        assert_eq!(
+            legacy,
+            "precision mediump float;\n#define color gl_FragColor\nvoid main() {\n    color = vec4(1.0);\n}\n"
+        );
+        assert_eq!(legacy.lines().count(), source.lines().count());
+    }
+
+    #[test]
+    fn legacy_source_keeps_vertex_outputs() {
+        let source = "in vec3 a_position;\nout vec4 color;";
+        assert_eq!(legacy_source(ShaderStage::Vertex, source), source);
+    }
+
+    #[test]
+    fn legacy_source_keeps_other_out_declarations() {
+        let source = "out vec3 color;\n// out vec4 color;\n";
+        assert_eq!(legacy_source(ShaderStage::Fragment, source), source);
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        assert_eq!(parse_log_line(""), None);
+        assert_eq!(parse_log_line("Compile failed."), None);
+        assert_eq!(parse_log_line("x:12: error"), None);
+        assert_eq!(parse_log_line("0:abc: error"), None);
+        assert_eq!(parse_log_line("0(abc) : error"), None);
+    }
+}
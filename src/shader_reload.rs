@@ -0,0 +1,220 @@
+//! Shader programs loaded from files on disk and recompiled when they change.
+//!
+//! Files are polled by modification time rather than watched through an OS
+//! notification API, which keeps this dependency free and works the same on
+//! every platform. A failed reload keeps the previous program active and
+//! stores the error so it can be shown in the UI until the file is fixed.
+//!
+//! Polling must not cost a redraw: event loops with an idle hook poll from
+//! there every [`POLL_INTERVAL`] and redraw only when
+//! [`ReloadableProgram::poll`] picked something up, while eframe, which has
+//! none, gets woken by a [`ShaderWatcher`] thread.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::assets;
+use crate::shader::{ShaderProgram, ShaderStage};
+
+/// How often shader files are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A [`ShaderProgram`] backed by source files that is rebuilt when they change.
+pub struct ReloadableProgram {
+    gl: Arc<glow::Context>,
+    version_header: String,
+    files: Vec<WatchedFile>,
+    program: Option<ShaderProgram>,
+    error: Option<String>,
+}
+
+struct WatchedFile {
+    stage: ShaderStage,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ReloadableProgram {
+    /// Compile the program from `files`. A failure is not fatal: it is
+    /// recorded in [`Self::error`] and compilation is retried on the next change.
+    pub fn load(
+        gl: Arc<glow::Context>,
+        version_header: &str,
+        files: &[(ShaderStage, &Path)],
+    ) -> Self {
+        let mut this = Self {
+            gl,
+            version_header: version_header.to_owned(),
+            files: files
+                .iter()
+                .map(|&(stage, path)| WatchedFile {
+                    stage,
+                    path: path.to_owned(),
+                    modified: None,
+                })
+                .collect(),
+            program: None,
+            error: None,
+        };
+        this.reload();
+        this
+    }
+
+    /// Like [`Self::load`], but the first compile has to succeed.
+    ///
+    /// A context whose GLSL cannot build the shaders at all is of no use, so
+    /// callers give up on it and fall back to another API or to software
+    /// rendering. Later edits are still allowed to fail.
+    pub fn load_required(
+        gl: Arc<glow::Context>,
+        version_header: &str,
+        files: &[(ShaderStage, &Path)],
+    ) -> Result<Self, String> {
+        let this = Self::load(gl, version_header, files);
+        match this.error {
+            Some(error) => Err(error),
+            None => Ok(this),
+        }
+    }
+
+    /// Recompile if any file changed since the last check.
+    ///
+    /// Returns `true` when a reload was attempted, whether or not it succeeded,
+    /// so the caller knows to redraw.
+    pub fn poll(&mut self) -> bool {
+        let changed = self
+            .files
+            .iter()
+            .any(|file| modified_time(&file.path) != file.modified);
+        if changed {
+            self.reload();
+        }
+        changed
+    }
+
+    /// The files the program is built from.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|file| file.path.as_path())
+    }
+
+    /// The most recent program that compiled successfully.
+    pub fn program(&self) -> Option<&ShaderProgram> {
+        self.program.as_ref()
+    }
+
+    /// The error from the last compile attempt, if it failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Show the current compile error, if any, in an egui window.
+    pub fn show_error_window(&self, ctx: &egui::Context) {
+        let Some(error) = &self.error else {
+            return;
+        };
+        egui::Window::new("Shader Error")
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                if self.program.is_some() {
+                    ui.label("Keeping the last working program until this is fixed.");
+                    ui.separator();
+                }
+                ui.label(
+                    egui::RichText::new(error)
+                        .monospace()
+                        .color(ui.visuals().error_fg_color),
+                );
+            });
+    }
+
+    fn reload(&mut self) {
+        let mut sources = Vec::with_capacity(self.files.len());
+        for file in &mut self.files {
+            file.modified = modified_time(&file.path);
+            match fs::read_to_string(&file.path) {
+                Ok(source) => sources.push((file.stage, source)),
+                Err(err) => {
+                    let error = format!("failed to read {}: {err}", file.path.display());
+                    log::error!("{error}");
+                    self.error = Some(error);
+                    return;
+                }
+            }
+        }
+
+        let sources: Vec<_> = sources
+            .iter()
+            .map(|(stage, source)| (*stage, source.as_str()))
+            .collect();
+        match ShaderProgram::new(self.gl.clone(), &self.version_header, &sources) {
+            Ok(program) => {
+                if self.program.is_some() {
+                    log::info!("Reloaded shader program from {}", self.describe_files());
+                }
+                self.program = Some(program);
+                self.error = None;
+            }
+            Err(err) => {
+                log::error!("{}: {err}", self.describe_files());
+                self.error = Some(err.to_string());
+            }
+        }
+    }
+
+    fn describe_files(&self) -> String {
+        self.files
+            .iter()
+            .map(|file| file.path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Path of a file in the `shaders` directory of the [asset root](assets::root).
+pub fn shader_path(name: &str) -> PathBuf {
+    assets::root().join("shaders").join(name)
+}
+
+/// Checks a set of shader files from a background thread every
+/// [`POLL_INTERVAL`] and calls back when one of them changed, so the UI
+/// thread only wakes up for actual edits. The thread stops once the watcher
+/// is dropped.
+pub struct ShaderWatcher {
+    changed: Arc<AtomicBool>,
+}
+
+impl ShaderWatcher {
+    /// Watch `paths`, calling `on_change` after each change, typically to
+    /// request a repaint.
+    pub fn spawn(paths: Vec<PathBuf>, on_change: impl Fn() + Send + 'static) -> Self {
+        let changed = Arc::new(AtomicBool::new(false));
+        let flag = changed.clone();
+        thread::spawn(move || {
+            let mut modified: Vec<_> = paths.iter().map(|path| modified_time(path)).collect();
+            // Once the watcher is gone, only this thread holds the flag
+            while Arc::strong_count(&flag) > 1 {
+                thread::sleep(POLL_INTERVAL);
+                let now: Vec<_> = paths.iter().map(|path| modified_time(path)).collect();
+                if now != modified {
+                    modified = now;
+                    flag.store(true, Ordering::Relaxed);
+                    on_change();
+                }
+            }
+        });
+        Self { changed }
+    }
+
+    /// Whether a file changed since the last call.
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+}
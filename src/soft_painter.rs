@@ -0,0 +1,414 @@
+//! An egui painter that draws on the CPU through [`crate::raster`], for
+//! running the UI on a softbuffer surface without any GL.
+//!
+//! The API mirrors `egui_glow::Painter` so the two are easy to swap. Like
+//! egui_glow on a non-sRGB framebuffer, all shading and blending happens on
+//! egui's premultiplied gamma-space colors.
+
+use std::collections::HashMap;
+
+use egui::epaint::textures::TexturesDelta;
+use egui::epaint::{ClippedPrimitive, ImageData, ImageDelta, Primitive, TextureId};
+use egui::{Color32, TextureFilter, TextureOptions, TextureWrapMode};
+
+use crate::raster::{Rect, Target, rasterize};
+
+/// A texture uploaded by egui, kept in CPU memory.
+struct Texture {
+    size: [usize; 2],
+    pixels: Vec<Color32>,
+    options: TextureOptions,
+}
+
+impl Texture {
+    /// Sample at normalized `uv`, returning premultiplied `0..=1` RGBA.
+    ///
+    /// There are no mipmaps, so minification uses the magnification filter.
+    fn sample(&self, [u, v]: [f32; 2]) -> [f32; 4] {
+        let [width, height] = self.size;
+        let (x, y) = (u * width as f32, v * height as f32);
+        match self.options.magnification {
+            TextureFilter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            TextureFilter::Linear => {
+                // Texel centers sit at half coordinates
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                let lerp = |a: [f32; 4], b: [f32; 4], t: f32| {
+                    std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)
+                };
+                let top = lerp(self.texel(x0, y0), self.texel(x0 + 1, y0), fx);
+                let bottom = lerp(self.texel(x0, y0 + 1), self.texel(x0 + 1, y0 + 1), fx);
+                lerp(top, bottom, fy)
+            }
+        }
+    }
+
+    fn texel(&self, x: i64, y: i64) -> [f32; 4] {
+        let [width, height] = self.size;
+        let wrap = |coord: i64, len: usize| {
+            let len = len as i64;
+            let coord = match self.options.wrap_mode {
+                TextureWrapMode::ClampToEdge => coord,
+                TextureWrapMode::Repeat => coord.rem_euclid(len),
+                TextureWrapMode::MirroredRepeat => {
+                    let coord = coord.rem_euclid(2 * len);
+                    if coord < len {
+                        coord
+                    } else {
+                        2 * len - 1 - coord
+                    }
+                }
+            };
+            coord.clamp(0, len - 1) as usize
+        };
+        to_rgba(self.pixels[wrap(y, height) * width + wrap(x, width)])
+    }
+}
+
+/// Paints egui output into a [`Target`].
+#[derive(Default)]
+pub struct SoftPainter {
+    textures: HashMap<TextureId, Texture>,
+}
+
+impl SoftPainter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply `textures_delta.set`, paint, then apply `textures_delta.free`.
+    pub fn paint_and_update_textures(
+        &mut self,
+        target: &mut Target,
+        pixels_per_point: f32,
+        clipped_primitives: &[ClippedPrimitive],
+        textures_delta: &TexturesDelta,
+    ) {
+        for (id, image_delta) in &textures_delta.set {
+            self.set_texture(*id, image_delta);
+        }
+
+        self.paint_primitives(target, pixels_per_point, clipped_primitives);
+
+        for id in &textures_delta.free {
+            self.free_texture(*id);
+        }
+    }
+
+    pub fn set_texture(&mut self, id: TextureId, delta: &ImageDelta) {
+        let ImageData::Color(image) = &delta.image;
+
+        match delta.pos {
+            None => {
+                self.textures.insert(
+                    id,
+                    Texture {
+                        size: image.size,
+                        pixels: image.pixels.clone(),
+                        options: delta.options,
+                    },
+                );
+            }
+            Some([x, y]) => {
+                let Some(texture) = self.textures.get_mut(&id) else {
+                    log::warn!("Partial update of unknown texture {id:?}");
+                    return;
+                };
+                texture.options = delta.options;
+
+                let [width, height] = image.size;
+                for (row, src) in image.pixels.chunks_exact(width).enumerate().take(height) {
+                    let start = (y + row) * texture.size[0] + x;
+                    texture.pixels[start..start + width].copy_from_slice(src);
+                }
+            }
+        }
+    }
+
+    pub fn free_texture(&mut self, id: TextureId) {
+        self.textures.remove(&id);
+    }
+
+    pub fn paint_primitives(
+        &mut self,
+        target: &mut Target,
+        pixels_per_point: f32,
+        clipped_primitives: &[ClippedPrimitive],
+    ) {
+        for ClippedPrimitive {
+            clip_rect,
+            primitive,
+        } in clipped_primitives
+        {
+            // Round the clip rect to whole pixels, the same way egui_glow sets
+            // up its scissor rect
+            let clip = Rect {
+                min: [
+                    (clip_rect.min.x * pixels_per_point).round() as i32,
+                    (clip_rect.min.y * pixels_per_point).round() as i32,
+                ],
+                max: [
+                    (clip_rect.max.x * pixels_per_point).round() as i32,
+                    (clip_rect.max.y * pixels_per_point).round() as i32,
+                ],
+            }
+            .intersect(target.bounds());
+            if clip.is_empty() {
+                continue;
+            }
+
+            match primitive {
+                Primitive::Mesh(mesh) => {
+                    let Some(texture) = self.textures.get(&mesh.texture_id) else {
+                        log::warn!("Failed to find texture {:?}", mesh.texture_id);
+                        continue;
+                    };
+                    paint_mesh(target, pixels_per_point, clip, mesh, texture);
+                }
+                Primitive::Callback(_) => {
+                    log::warn!("Paint callbacks need GL and are not supported by SoftPainter");
+                }
+            }
+        }
+    }
+}
+
+fn paint_mesh(
+    target: &mut Target,
+    pixels_per_point: f32,
+    clip: Rect,
+    mesh: &egui::Mesh,
+    texture: &Texture,
+) {
+    let width = target.width as usize;
+
+    for triangle in mesh.indices.chunks_exact(3) {
+        let vertices = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+        let positions = vertices.map(|v| [v.pos.x * pixels_per_point, v.pos.y * pixels_per_point]);
+        let colors = vertices.map(|v| to_rgba(v.color));
+
+        rasterize(positions, clip, |x, y, weights| {
+            let mut uv = [0.0; 2];
+            let mut color = [0.0; 4];
+            for ((vertex, vertex_color), weight) in vertices.iter().zip(&colors).zip(weights) {
+                uv[0] += vertex.uv.x * weight;
+                uv[1] += vertex.uv.y * weight;
+                for (channel, value) in color.iter_mut().zip(vertex_color) {
+                    *channel += value * weight;
+                }
+            }
+
+            let texel = texture.sample(uv);
+            let src: [f32; 4] = std::array::from_fn(|i| color[i] * texel[i]);
+            if src[3] <= 0.0 && src[..3].iter().all(|&c| c <= 0.0) {
+                return;
+            }
+
+            // Premultiplied "over": src + dst * (1 - src_alpha)
+            let pixel = &mut target.pixels[y as usize * width + x as usize];
+            let [_, r, g, b] = pixel.to_be_bytes();
+            let inverse_alpha = 1.0 - src[3].min(1.0);
+            let blend = |channel: f32, dst: u8| {
+                let value = channel + f32::from(dst) / 255.0 * inverse_alpha;
+                (value.clamp(0.0, 1.0) * 255.0 + 0.5) as u32
+            };
+            *pixel = (blend(src[0], r) << 16) | (blend(src[1], g) << 8) | blend(src[2], b);
+        });
+    }
+}
+
+fn to_rgba(color: Color32) -> [f32; 4] {
+    color.to_array().map(|c| f32::from(c) / 255.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::epaint::ColorImage;
+    use egui::{Pos2, pos2};
+
+    use super::*;
+
+    const RED: Color32 = Color32::RED;
+    const GREEN: Color32 = Color32::GREEN;
+    const BLUE: Color32 = Color32::BLUE;
+    const WHITE: Color32 = Color32::WHITE;
+
+    fn texture(
+        size: [usize; 2],
+        pixels: Vec<Color32>,
+        filter: TextureFilter,
+        wrap_mode: TextureWrapMode,
+    ) -> Texture {
+        Texture {
+            size,
+            pixels,
+            options: TextureOptions {
+                magnification: filter,
+                minification: filter,
+                wrap_mode,
+                ..TextureOptions::default()
+            },
+        }
+    }
+
+    fn assert_rgba_eq(actual: [f32; 4], expected: [f32; 4]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn nearest_picks_the_texel_under_uv() {
+        let texture = texture(
+            [2, 2],
+            vec![RED, GREEN, BLUE, WHITE],
+            TextureFilter::Nearest,
+            TextureWrapMode::ClampToEdge,
+        );
+        assert_eq!(texture.sample([0.25, 0.25]), to_rgba(RED));
+        assert_eq!(texture.sample([0.75, 0.25]), to_rgba(GREEN));
+        assert_eq!(texture.sample([0.25, 0.75]), to_rgba(BLUE));
+        assert_eq!(texture.sample([0.99, 0.99]), to_rgba(WHITE));
+    }
+
+    #[test]
+    fn linear_blends_between_texel_centers() {
+        let texture = texture(
+            [2, 1],
+            vec![Color32::BLACK, WHITE],
+            TextureFilter::Linear,
+            TextureWrapMode::ClampToEdge,
+        );
+        // Exactly on a texel center, then halfway between the two
+        assert_rgba_eq(texture.sample([0.25, 0.5]), [0.0, 0.0, 0.0, 1.0]);
+        assert_rgba_eq(texture.sample([0.75, 0.5]), [1.0; 4]);
+        assert_rgba_eq(texture.sample([0.5, 0.5]), [0.5, 0.5, 0.5, 1.0]);
+        // Clamped past the outer centers
+        assert_rgba_eq(texture.sample([0.0, 0.5]), [0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn wrap_modes() {
+        let row = |wrap_mode| {
+            let texture = texture(
+                [3, 1],
+                vec![RED, GREEN, BLUE],
+                TextureFilter::Nearest,
+                wrap_mode,
+            );
+            [-2, -1, 0, 2, 3, 4].map(|x| texture.texel(x, 0))
+        };
+        let [r, g, b] = [RED, GREEN, BLUE].map(to_rgba);
+        assert_eq!(row(TextureWrapMode::ClampToEdge), [r, r, r, b, b, b]);
+        assert_eq!(row(TextureWrapMode::Repeat), [g, b, r, b, r, g]);
+        assert_eq!(row(TextureWrapMode::MirroredRepeat), [g, r, r, b, b, g]);
+    }
+
+    #[test]
+    fn partial_update_replaces_only_its_rect() {
+        let id = TextureId::Managed(0);
+        let mut painter = SoftPainter::new();
+        let options = TextureOptions::NEAREST;
+        painter.set_texture(
+            id,
+            &ImageDelta::full(ColorImage::filled([3, 2], RED), options),
+        );
+        painter.set_texture(
+            id,
+            &ImageDelta::partial(
+                [1, 1],
+                ColorImage::new([2, 1], vec![GREEN, BLUE]),
+                TextureOptions::LINEAR,
+            ),
+        );
+
+        let texture = &painter.textures[&id];
+        assert_eq!(texture.size, [3, 2]);
+        assert_eq!(texture.pixels, [RED, RED, RED, RED, GREEN, BLUE]);
+        assert_eq!(texture.options, TextureOptions::LINEAR);
+    }
+
+    #[test]
+    fn partial_update_of_an_unknown_texture_is_ignored() {
+        let mut painter = SoftPainter::new();
+        let delta = ImageDelta::partial(
+            [0, 0],
+            ColorImage::filled([1, 1], RED),
+            TextureOptions::NEAREST,
+        );
+        painter.set_texture(TextureId::Managed(1), &delta);
+        assert!(painter.textures.is_empty());
+    }
+
+    /// Paint one rect in `color` over a 4x4 target cleared to `background`,
+    /// clipped to `clip`, and return the target.
+    fn paint_rect(background: [f32; 3], color: Color32, clip: egui::Rect) -> Vec<u32> {
+        let id = TextureId::Managed(0);
+        let mut painter = SoftPainter::new();
+        painter.set_texture(
+            id,
+            &ImageDelta::full(ColorImage::filled([1, 1], WHITE), TextureOptions::NEAREST),
+        );
+        let mut mesh = egui::Mesh::with_texture(id);
+        mesh.add_rect_with_uv(
+            egui::Rect::from_min_max(Pos2::ZERO, pos2(4.0, 4.0)),
+            egui::Rect::from_min_max(Pos2::ZERO, pos2(1.0, 1.0)),
+            color,
+        );
+
+        let mut pixels = vec![0; 16];
+        let mut target = Target::new(&mut pixels, 4, 4);
+        target.clear(background);
+        painter.paint_primitives(
+            &mut target,
+            1.0,
+            &[ClippedPrimitive {
+                clip_rect: clip,
+                primitive: Primitive::Mesh(mesh),
+            }],
+        );
+        pixels
+    }
+
+    #[test]
+    fn blends_premultiplied_over() {
+        let everything = egui::Rect::EVERYTHING;
+        // Half transparent red over blue: src + dst * (1 - src_alpha)
+        let half_red = Color32::from_rgba_premultiplied(128, 0, 0, 128);
+        let pixels = paint_rect([0.0, 0.0, 1.0], half_red, everything);
+        assert!(
+            pixels.iter().all(|&pixel| pixel == 0x80_00_7F),
+            "{pixels:x?}"
+        );
+
+        // Opaque replaces, transparent leaves the target alone
+        let pixels = paint_rect([0.0, 0.0, 1.0], GREEN, everything);
+        assert!(pixels.iter().all(|&pixel| pixel == 0x00_FF_00));
+        let pixels = paint_rect([0.0, 0.0, 1.0], Color32::TRANSPARENT, everything);
+        assert!(pixels.iter().all(|&pixel| pixel == 0x00_00_FF));
+
+        // Additive: premultiplied color with zero alpha still adds light
+        let additive = Color32::from_rgba_premultiplied(255, 0, 0, 0);
+        let pixels = paint_rect([0.0, 0.0, 1.0], additive, everything);
+        assert!(pixels.iter().all(|&pixel| pixel == 0xFF_00_FF));
+    }
+
+    #[test]
+    fn clips_to_the_primitive_rect() {
+        let clip = egui::Rect::from_min_max(pos2(1.0, 1.0), pos2(3.0, 2.0));
+        let pixels = paint_rect([0.0; 3], WHITE, clip);
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = if (1..3).contains(&x) && y == 1 {
+                    0xFF_FF_FF
+                } else {
+                    0
+                };
+                assert_eq!(pixels[y * 4 + x], expected, "pixel {x}, {y}");
+            }
+        }
+    }
+}
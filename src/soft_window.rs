@@ -0,0 +1,96 @@
+//! A winit window presented through softbuffer, the CPU counterpart of
+//! [`crate::gl_window`].
+//!
+//! Used by the softbuffer demo and as the fallback when a GL window cannot be
+//! created. Frames are drawn with [`crate::raster`] into the window's buffer.
+
+use std::error::Error;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use softbuffer::{Context, SoftBufferError, Surface};
+use winit::error::OsError;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowAttributes};
+
+use crate::raster::Target;
+
+/// A winit window together with a softbuffer surface presenting into it.
+pub struct SoftWindow {
+    pub window: Arc<Window>,
+    surface: Surface<Arc<Window>, Arc<Window>>,
+}
+
+impl SoftWindow {
+    pub fn new(
+        event_loop: &ActiveEventLoop,
+        window_attributes: WindowAttributes,
+    ) -> Result<Self, SoftWindowError> {
+        let window = Arc::new(event_loop.create_window(window_attributes)?);
+        let context = Context::new(Arc::clone(&window))?;
+        let surface = Surface::new(&context, Arc::clone(&window))?;
+        Ok(Self { window, surface })
+    }
+
+    /// Draw a frame at the window's current size and present it.
+    ///
+    /// Returns `None` without drawing while the window is zero sized (minimized).
+    pub fn present<R>(
+        &mut self,
+        draw: impl FnOnce(&mut Target) -> R,
+    ) -> Result<Option<R>, SoftBufferError> {
+        let size = self.window.inner_size();
+        let (Some(width), Some(height)) =
+            (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+        else {
+            return Ok(None);
+        };
+        self.surface.resize(width, height)?;
+
+        let mut buffer = self.surface.buffer_mut()?;
+        let result = draw(&mut Target::new(&mut buffer, width.get(), height.get()));
+
+        self.window.pre_present_notify();
+        buffer.present()?;
+        Ok(Some(result))
+    }
+}
+
+#[derive(Debug)]
+pub enum SoftWindowError {
+    /// winit failed to create the window.
+    Window(OsError),
+    /// softbuffer failed to create its context or surface.
+    Softbuffer(SoftBufferError),
+}
+
+impl fmt::Display for SoftWindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Window(err) => write!(f, "failed to create window: {err}"),
+            Self::Softbuffer(err) => write!(f, "failed to create softbuffer surface: {err}"),
+        }
+    }
+}
+
+impl Error for SoftWindowError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Window(err) => Some(err),
+            Self::Softbuffer(err) => Some(err),
+        }
+    }
+}
+
+impl From<OsError> for SoftWindowError {
+    fn from(err: OsError) -> Self {
+        Self::Window(err)
+    }
+}
+
+impl From<SoftBufferError> for SoftWindowError {
+    fn from(err: SoftBufferError) -> Self {
+        Self::Softbuffer(err)
+    }
+}
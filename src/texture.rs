@@ -0,0 +1,318 @@
+//! 2D textures loaded from image files, for the demos' own rendering (egui
+//! manages its textures itself).
+//!
+//! Images are uploaded with the first row of the file at `t = 0`, so texture
+//! coordinates follow image conventions (origin top-left), like glTF's.
+//!
+//! Color images are stored as sRGB (`GL_SRGB8_ALPHA8`), so sampling returns
+//! linear values and filtering and mipmap generation average light rather
+//! than gamma-encoded numbers. Data textures (normals, masks, lookup tables)
+//! should use [`ColorSpace::Linear`]. Since the demos render to non-sRGB
+//! framebuffers, a shader sampling an sRGB texture encodes its result back
+//! to sRGB before writing it out.
+
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use glow::HasContext;
+use image::RgbaImage;
+
+use crate::assets;
+use crate::shader::ShaderProgram;
+
+/// Path of a file in the `assets/textures` directory of the
+/// [asset root](assets::root).
+pub fn texture_path(name: &str) -> PathBuf {
+    assets::root().join("assets").join("textures").join(name)
+}
+
+/// How texel values are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Gamma-encoded color, decoded to linear by the sampler.
+    #[default]
+    Srgb,
+    /// Values are used as stored.
+    Linear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+}
+
+impl Wrap {
+    pub const ALL: [Wrap; 3] = [Self::Repeat, Self::MirroredRepeat, Self::ClampToEdge];
+
+    fn gl_enum(self) -> u32 {
+        match self {
+            Self::Repeat => glow::REPEAT,
+            Self::MirroredRepeat => glow::MIRRORED_REPEAT,
+            Self::ClampToEdge => glow::CLAMP_TO_EDGE,
+        }
+    }
+}
+
+/// Named filtering presets, from blockiest to smoothest. See
+/// [`Sampling::preset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterPreset {
+    Nearest,
+    Bilinear,
+    Trilinear,
+}
+
+impl FilterPreset {
+    pub const ALL: [FilterPreset; 3] = [Self::Nearest, Self::Bilinear, Self::Trilinear];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Nearest => "nearest",
+            Self::Bilinear => "bilinear",
+            Self::Trilinear => "trilinear",
+        }
+    }
+
+    /// The min/mag filter and the mipmap filter.
+    fn filters(self) -> (Filter, Option<Filter>) {
+        match self {
+            Self::Nearest => (Filter::Nearest, None),
+            Self::Bilinear => (Filter::Linear, None),
+            Self::Trilinear => (Filter::Linear, Some(Filter::Linear)),
+        }
+    }
+}
+
+/// Filtering and wrapping, changeable after upload with
+/// [`Texture2D::set_sampling`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sampling {
+    /// Filter used when a texel covers less than a pixel.
+    pub min_filter: Filter,
+    /// Filter used when a texel covers more than a pixel.
+    pub mag_filter: Filter,
+    /// How to pick between mipmap levels when minifying; `None` samples only
+    /// the full-size image.
+    pub mipmap_filter: Option<Filter>,
+    /// Applied to both axes.
+    pub wrap: Wrap,
+}
+
+impl Default for Sampling {
+    /// Trilinear filtering, repeating.
+    fn default() -> Self {
+        Self {
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmap_filter: Some(Filter::Linear),
+            wrap: Wrap::Repeat,
+        }
+    }
+}
+
+impl Sampling {
+    /// The filters of `preset` with `wrap`.
+    pub fn preset(preset: FilterPreset, wrap: Wrap) -> Self {
+        let (filter, mipmap_filter) = preset.filters();
+        Self {
+            min_filter: filter,
+            mag_filter: filter,
+            mipmap_filter,
+            wrap,
+        }
+    }
+
+    fn gl_min_filter(&self) -> u32 {
+        match (self.min_filter, self.mipmap_filter) {
+            (Filter::Nearest, None) => glow::NEAREST,
+            (Filter::Linear, None) => glow::LINEAR,
+            (Filter::Nearest, Some(Filter::Nearest)) => glow::NEAREST_MIPMAP_NEAREST,
+            (Filter::Nearest, Some(Filter::Linear)) => glow::NEAREST_MIPMAP_LINEAR,
+            (Filter::Linear, Some(Filter::Nearest)) => glow::LINEAR_MIPMAP_NEAREST,
+            (Filter::Linear, Some(Filter::Linear)) => glow::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    fn gl_mag_filter(&self) -> u32 {
+        match self.mag_filter {
+            Filter::Nearest => glow::NEAREST,
+            Filter::Linear => glow::LINEAR,
+        }
+    }
+}
+
+/// An RGBA texture with a full mipmap chain, deleted when dropped.
+pub struct Texture2D {
+    gl: Arc<glow::Context>,
+    texture: glow::Texture,
+    size: [u32; 2],
+    color_space: ColorSpace,
+    sampling: Sampling,
+}
+
+impl Texture2D {
+    /// Decode a PNG or JPEG file and upload it.
+    pub fn load(
+        gl: Arc<glow::Context>,
+        path: &Path,
+        color_space: ColorSpace,
+        sampling: Sampling,
+    ) -> Result<Self, TextureError> {
+        let image = image::open(path)?.into_rgba8();
+        Self::new(gl, &image, color_space, sampling)
+    }
+
+    /// Upload `image` and generate its mipmaps.
+    ///
+    /// OpenGL ES 2.0 has no sRGB textures; there `color_space` falls back to
+    /// [`ColorSpace::Linear`], see [`Texture2D::color_space`].
+    pub fn new(
+        gl: Arc<glow::Context>,
+        image: &RgbaImage,
+        mut color_space: ColorSpace,
+        sampling: Sampling,
+    ) -> Result<Self, TextureError> {
+        let version = gl.version();
+        let gles2 = version.is_embedded && version.major < 3;
+        if gles2 && color_space == ColorSpace::Srgb {
+            log::warn!("OpenGL ES 2.0 has no sRGB textures, sampling without decoding");
+            color_space = ColorSpace::Linear;
+        }
+        // ES 2.0 only knows unsized formats
+        let internal_format = match color_space {
+            ColorSpace::Srgb => glow::SRGB8_ALPHA8,
+            ColorSpace::Linear if gles2 => glow::RGBA,
+            ColorSpace::Linear => glow::RGBA8,
+        };
+
+        let texture = unsafe {
+            let texture = gl.create_texture().map_err(TextureError::Gl)?;
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                internal_format as i32,
+                image.width() as i32,
+                image.height() as i32,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(Some(image.as_raw())),
+            );
+            gl.generate_mipmap(glow::TEXTURE_2D);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            texture
+        };
+
+        let mut texture = Self {
+            gl,
+            texture,
+            size: [image.width(), image.height()],
+            color_space,
+            sampling,
+        };
+        texture.apply_sampling();
+        Ok(texture)
+    }
+
+    pub fn raw(&self) -> glow::Texture {
+        self.texture
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    /// The color space the texture was actually created with.
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    pub fn sampling(&self) -> Sampling {
+        self.sampling
+    }
+
+    pub fn set_sampling(&mut self, sampling: Sampling) {
+        if sampling != self.sampling {
+            self.sampling = sampling;
+            self.apply_sampling();
+        }
+    }
+
+    fn apply_sampling(&mut self) {
+        let gl = &self.gl;
+        let sampling = &self.sampling;
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+            for (parameter, value) in [
+                (glow::TEXTURE_MIN_FILTER, sampling.gl_min_filter()),
+                (glow::TEXTURE_MAG_FILTER, sampling.gl_mag_filter()),
+                (glow::TEXTURE_WRAP_S, sampling.wrap.gl_enum()),
+                (glow::TEXTURE_WRAP_T, sampling.wrap.gl_enum()),
+            ] {
+                gl.tex_parameter_i32(glow::TEXTURE_2D, parameter, value as i32);
+            }
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+    }
+
+    /// Bind to texture `unit` and point `program`'s `sampler` uniform at it.
+    /// `program` must be in use.
+    pub fn bind(&self, program: &ShaderProgram, sampler: &str, unit: u32) {
+        let gl = &self.gl;
+        unsafe {
+            gl.active_texture(glow::TEXTURE0 + unit);
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+            gl.uniform_1_i32(program.uniform_location(sampler).as_ref(), unit as i32);
+        }
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_texture(self.texture) };
+    }
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    /// The file could not be read or decoded.
+    Image(image::ImageError),
+    /// The driver refused to create the texture object.
+    Gl(String),
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Image(err) => write!(f, "failed to load texture image: {err}"),
+            Self::Gl(err) => write!(f, "failed to create texture: {err}"),
+        }
+    }
+}
+
+impl Error for TextureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Image(err) => Some(err),
+            Self::Gl(_) => None,
+        }
+    }
+}
+
+impl From<image::ImageError> for TextureError {
+    fn from(err: image::ImageError) -> Self {
+        Self::Image(err)
+    }
+}
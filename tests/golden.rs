@@ -0,0 +1,254 @@
+//! Golden-image tests: render each demo with `winit-test <demo> --headless`
+//! and compare the result against the reference PNGs in `tests/golden`.
+//!
+//! Run with `UPDATE_GOLDEN=1` to rewrite the references from the current
+//! output. On a mismatch the actual image and a diff highlighting the
+//! offending pixels in red are written to `target/tmp/golden`.
+#![cfg(not(target_vendor = "apple"))]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use image::{Rgba, RgbaImage};
+
+/// Largest per-channel difference for a pixel to still count as matching.
+const TOLERANCE: u8 = 8;
+
+/// Fraction of pixels allowed to exceed [`TOLERANCE`], to absorb rasterizer
+/// differences along triangle edges and glyph outlines.
+const MAX_MISMATCHED_FRACTION: f64 = 0.001;
+
+/// Enough frames for egui windows to finish their sizing pass and fade in.
+const FRAMES: &str = "10";
+
+#[test]
+fn glow_run_gradient_triangle() {
+    let actual = render("glow", "glow_run", &[]);
+    assert_matches_golden("glow_run", &actual);
+}
+
+/// A JPEG texture, sRGB decoded, with trilinear filtering and repeat wrapping.
+#[test]
+fn glow_run_textured_quad() {
+    let actual = render("glow", "glow_run_textured_quad", &["--textured-quad"]);
+    assert_matches_golden("glow_run_textured_quad", &actual);
+}
+
+/// GLSL ES 1.00 gets the same shaders through the legacy prelude, so an
+/// OpenGL ES 2.0 context has to draw the same picture.
+#[test]
+fn glow_run_textured_quad_gles2_matches_gl() {
+    let actual = render(
+        "glow",
+        "glow_run_textured_quad_gles2",
+        &["--textured-quad", "--gl", "gles2.0"],
+    );
+    assert_matches(
+        &golden_path("glow_run_textured_quad"),
+        "glow_run_textured_quad_gles2",
+        &actual,
+    );
+}
+
+#[test]
+fn ai_one_color_picker() {
+    let actual = render("egui-manual", "ai_one", &["--show-color-picker"]);
+    assert_matches_golden("ai_one", &actual);
+}
+
+/// The softbuffer fallback has to look like the GL path it replaces.
+#[test]
+fn ai_one_software_matches_gl() {
+    let actual = render(
+        "egui-manual",
+        "ai_one_software",
+        &["--show-color-picker", "--software"],
+    );
+    assert_matches(&golden_path("ai_one"), "ai_one_software", &actual);
+}
+
+/// The gallery's side panel, with a scene switched to from the command line.
+#[test]
+fn ai_one_gallery_textured_quad() {
+    let actual = render(
+        "egui-manual",
+        "ai_one_gallery",
+        &["--gallery", "--scene", "textured-quad"],
+    );
+    assert_matches_golden("ai_one_gallery", &actual);
+}
+
+#[test]
+fn ai_one_obj_model_gles2_matches_gl() {
+    let actual = render(
+        "egui-manual",
+        "ai_one_checker_cube_gles2",
+        &[
+            "--model",
+            &model_path("checker_cube.obj"),
+            "--gl",
+            "gles2.0",
+        ],
+    );
+    assert_matches(
+        &golden_path("ai_one_checker_cube"),
+        "ai_one_checker_cube_gles2",
+        &actual,
+    );
+}
+
+#[test]
+fn eframe_winit_scene_texture() {
+    let actual = render("eframe", "eframe_winit", &["--show-color-picker"]);
+    assert_matches_golden("eframe_winit", &actual);
+}
+
+#[test]
+fn ai_one_obj_model() {
+    let actual = render(
+        "egui-manual",
+        "ai_one_checker_cube",
+        &["--model", &model_path("checker_cube.obj")],
+    );
+    assert_matches_golden("ai_one_checker_cube", &actual);
+}
+
+#[test]
+fn eframe_winit_glb_model() {
+    let actual = render(
+        "eframe",
+        "eframe_winit_pyramid",
+        &["--model", &model_path("pyramid.glb")],
+    );
+    assert_matches_golden("eframe_winit_pyramid", &actual);
+}
+
+/// The embedded `.gltf` holds the same data as the `.glb`, base64 encoded.
+#[test]
+fn eframe_winit_embedded_gltf_matches_glb() {
+    let actual = render(
+        "eframe",
+        "eframe_winit_pyramid_embedded",
+        &["--model", &model_path("pyramid_embedded.gltf")],
+    );
+    assert_matches(
+        &golden_path("eframe_winit_pyramid"),
+        "eframe_winit_pyramid_embedded",
+        &actual,
+    );
+}
+
+/// The software rasterizer is meant to be a reference for the GPU path, so it
+/// is checked against `glow_run`'s golden rather than getting its own.
+#[test]
+fn softbuffer_matches_glow_run() {
+    let actual = render("softbuffer", "softbuffer", &[]);
+    assert_matches(&golden_path("glow_run"), "softbuffer", &actual);
+}
+
+#[test]
+fn softbuffer_egui_color_picker() {
+    let actual = render("softbuffer", "softbuffer_egui", &["--show-color-picker"]);
+    assert_matches_golden("softbuffer_egui", &actual);
+}
+
+fn output_dir() -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    std::fs::create_dir_all(&dir).expect("create golden output dir");
+    dir
+}
+
+/// Run the launcher's `demo` subcommand headless with `args`.
+fn render(demo: &str, name: &str, args: &[&str]) -> RgbaImage {
+    let output = output_dir().join(format!("{name}.png"));
+    let status = Command::new(env!("CARGO_BIN_EXE_winit-test"))
+        .arg(demo)
+        .args(["--headless", "--frames", FRAMES, "--output"])
+        .arg(&output)
+        .args(args)
+        .status()
+        .unwrap_or_else(|err| panic!("failed to run winit-test {demo}: {err}"));
+    assert!(status.success(), "winit-test {demo} exited with {status}");
+
+    image::open(&output)
+        .unwrap_or_else(|err| panic!("failed to read {}: {err}", output.display()))
+        .into_rgba8()
+}
+
+fn model_path(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets/models")
+        .join(name);
+    path.to_string_lossy().into_owned()
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"))
+}
+
+fn assert_matches_golden(name: &str, actual: &RgbaImage) {
+    let golden_path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        actual.save(&golden_path).expect("write golden image");
+        return;
+    }
+    assert_matches(&golden_path, name, actual);
+}
+
+fn assert_matches(golden_path: &Path, name: &str, actual: &RgbaImage) {
+    let expected = image::open(golden_path)
+        .unwrap_or_else(|err| {
+            panic!(
+                "failed to read {}: {err} (run with UPDATE_GOLDEN=1 to create it)",
+                golden_path.display()
+            )
+        })
+        .into_rgba8();
+    assert_eq!(
+        expected.dimensions(),
+        actual.dimensions(),
+        "{name}: image size differs from golden"
+    );
+
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+    let mut mismatched = 0_u64;
+    for ((expected, actual), diff) in expected
+        .pixels()
+        .zip(actual.pixels())
+        .zip(diff.pixels_mut())
+    {
+        let matches = expected
+            .0
+            .iter()
+            .zip(actual.0)
+            .all(|(&e, a)| e.abs_diff(a) <= TOLERANCE);
+        *diff = if matches {
+            // Dimmed greyscale of the expected image for context
+            let luma =
+                (u16::from(expected[0]) + u16::from(expected[1]) + u16::from(expected[2])) / 12;
+            Rgba([luma as u8, luma as u8, luma as u8, 255])
+        } else {
+            mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        };
+    }
+
+    let total = u64::from(actual.width()) * u64::from(actual.height());
+    let fraction = mismatched as f64 / total as f64;
+    if fraction > MAX_MISMATCHED_FRACTION {
+        let dir = output_dir();
+        let actual_path = dir.join(format!("{name}.actual.png"));
+        let diff_path = dir.join(format!("{name}.diff.png"));
+        actual.save(&actual_path).expect("write actual image");
+        diff.save(&diff_path).expect("write diff image");
+        panic!(
+            "{name}: {mismatched} of {total} pixels differ from {} by more than {TOLERANCE}\n  \
+             actual: {}\n  diff:   {}",
+            golden_path.display(),
+            actual_path.display(),
+            diff_path.display(),
+        );
+    }
+}